@@ -0,0 +1,191 @@
+use crate::battle_logic::{healed, lifeness, Ally, Battlable, BattleAction, BattleState, Target};
+use crate::damage::{self, Damage, DamageType};
+use crate::enemies::Lifeness;
+use crate::level::Biome;
+use crate::player::{buffed, status_buffs, Player, Stats, StatusEffect};
+use crate::spells::{self, Mana};
+use crate::zone::ZoneType;
+use rand::prelude::*;
+use std::sync::Arc;
+
+/// the most allies the player can travel with at once.
+pub const MAX_PARTY: usize = 3;
+/// the level Log and Exp buffs are evaluated at for allies.
+pub const ALLY_LEVEL: u8 = 1;
+/// companions heal whoever drops below this share of their max health.
+const WOUNDED: f32 = 0.4;
+
+/// someone the player can recruit to their party.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Hireling {
+    pub name: &'static str,
+    pub stats: Stats,
+    /// how much mana the hireling has, healers need it for their spells.
+    pub mana: u8,
+}
+
+const fn hireling_entry(
+    name: &'static str,
+    [hp, str, mg_str, def, mg_def, speed]: [u8; 6],
+    mana: u8,
+) -> Hireling {
+    Hireling {
+        name,
+        stats: Stats {
+            hp,
+            str,
+            mg_str,
+            def,
+            mg_def,
+            speed,
+        },
+        mana,
+    }
+}
+
+// hireling_entry(name, [hp, str, mg_str, def, mg_def, speed], mana)
+const HIRELINGS: [Hireling; 5] = [
+    hireling_entry("Sellsword", [18, 5, 0, 4, 1, 4], 0),
+    hireling_entry("Archer", [14, 5, 0, 2, 2, 6], 0),
+    hireling_entry("Squire", [16, 4, 0, 5, 2, 3], 0),
+    hireling_entry("Hedge Witch", [12, 1, 4, 2, 4, 4], 10),
+    hireling_entry("Wandering Cleric", [14, 2, 4, 3, 4, 3], 12),
+];
+
+/// the companion waiting to be recruited in a zone, if any. every Sanctuary has one, and so does
+/// every other Shelter in a Town.
+pub fn hireling(biome: Biome, kind: &ZoneType, seed: u64) -> Option<&'static Hireling> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    match kind {
+        ZoneType::Sanctuary => HIRELINGS.choose(&mut rng),
+        ZoneType::Shelter if matches!(biome, Biome::Town) && rng.gen_bool(0.5) => {
+            HIRELINGS.choose(&mut rng)
+        }
+        _ => None,
+    }
+}
+
+/// an ally controlled by the game. hirelings stay in the players party, summoned creatures fade
+/// once the battle is over.
+pub struct Companion {
+    name: Arc<str>,
+    stats: Stats,
+    /// current health
+    hp: u8,
+    mana: Mana,
+    status: Vec<Box<dyn StatusEffect>>,
+    /// true for creatures summoned by a spell.
+    summoned: bool,
+}
+
+impl Companion {
+    /// makes a companion from a hireling entry.
+    pub fn hire(entry: &Hireling) -> Self {
+        Self {
+            name: Arc::from(entry.name),
+            hp: entry.stats.hp,
+            stats: entry.stats.clone(),
+            mana: Mana::new(entry.mana),
+            status: Vec::new(),
+            summoned: false,
+        }
+    }
+
+    /// makes a creature that fights for its summoner until the end of the battle.
+    pub fn summoned(name: &str, stats: Stats) -> Self {
+        Self {
+            name: Arc::from(name),
+            hp: stats.hp,
+            stats,
+            mana: Mana::new(0),
+            status: Vec::new(),
+            summoned: true,
+        }
+    }
+
+    fn healer(&self) -> bool {
+        self.stats.mg_str > self.stats.str
+    }
+}
+
+fn wounded(who: &dyn Battlable) -> bool {
+    (who.get_hp() as f32) < who.get_max_hp() as f32 * WOUNDED
+}
+
+/// attacks the enemy closest to falling, with whichever kind of strength `stats` has more of.
+fn strike(state: &BattleState, stats: &Stats) -> BattleAction {
+    let kind = if stats.mg_str > stats.str {
+        DamageType::Magical
+    } else {
+        DamageType::Physical
+    };
+
+    match state.weakest_enemy() {
+        Some(i) => BattleAction::Attack((kind, Target::Enemy(i))),
+        None => BattleAction::Defend,
+    }
+}
+
+impl Ally for Companion {
+    fn get_move(&mut self, state: &mut BattleState, player: &Player) -> BattleAction {
+        if self.healer() {
+            // the companion is out of the battle while it decides, so it can only be sure of
+            // where the player and itself are.
+            let target = if wounded(player) {
+                Some(Target::Ally(0))
+            } else if wounded(self) {
+                Some(Target::TheSelf)
+            } else {
+                None
+            };
+
+            if let (Some(target), Some(heal)) = (target, spells::learn("Heal")) {
+                if self.mana.check(heal.as_ref()).is_ok() {
+                    return BattleAction::CastSpell((heal, target));
+                }
+            }
+        }
+
+        strike(state, &self.stats)
+    }
+
+    fn persists(&self) -> bool {
+        !self.summoned
+    }
+}
+
+impl Battlable for Companion {
+    fn get_name(&self) -> Arc<str> {
+        self.name.clone()
+    }
+
+    fn get_hp(&self) -> u8 {
+        self.hp
+    }
+
+    fn get_stats(&self) -> Stats {
+        buffed(&self.stats, status_buffs(&self.status).iter(), ALLY_LEVEL)
+    }
+
+    fn apply_damage(&mut self, dmg: Damage) -> Lifeness {
+        self.hp = damage::apply(self.hp, &dmg);
+
+        lifeness(self.hp)
+    }
+
+    fn heal(&mut self, amount: u8) -> u8 {
+        let (hp, restored) = healed(self.hp, self.stats.hp, amount);
+        self.hp = hp;
+
+        restored
+    }
+
+    fn status_effects(&mut self) -> &mut Vec<Box<dyn StatusEffect>> {
+        &mut self.status
+    }
+
+    fn mana(&mut self) -> Option<&mut Mana> {
+        Some(&mut self.mana)
+    }
+}
@@ -1,47 +1,937 @@
+use crate::damage::{self, Damage, DamageType};
+use crate::enemies::Lifeness;
+use crate::experience;
 use crate::game::GameState;
+use crate::items::{self, Category, ItemKey, Use};
+use crate::player::{Stacking, Stats, StatusEffect};
+use crate::spells::{self, Mana, School, TargetMode};
+use crate::status;
+use crate::world::World;
 use crate::Enemy;
-use crate::Item;
 use crate::Player;
+use rand::prelude::*;
+use rand::rngs::StdRng;
 use std::sync::Arc;
 
-/// describes the target of an attack or spell.
+/// describes the target of an attack or spell. targets are relative to whoever is acting. the
+/// players side of the battle is numbered with the player first and their allies after, so for an
+/// enemy `Enemy(0)` is the player and `Enemy(1)` is the players first ally, and for the player (or
+/// one of their allies) `Ally(0)` is the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Target {
     /// indicates an attack or spell that targets the self
     TheSelf,
     /// indicates an attack or spell that targets an enemy. which enemy is defined by the usize
     Enemy(usize),
+    /// indicates a spell that targets an ally. which ally is defined by the usize
+    Ally(usize),
 }
 
 pub enum BattleAction {
-    /// indicates that the entity is attacking, holds a u8 of how much damage is being done
-    Attack(u8),
+    /// indicates that the entity is attacking, holds the kind of damage being done and who it is
+    /// done to. how much damage is worked out from the attacker and targets stats.
+    Attack((DamageType, Target)),
     /// indicates the casting of a spell
     CastSpell((Box<dyn Spell>, Target)),
     /// indicates Fleeing (will end the battle)
     Flee,
-    /// indicates the usage of an item
-    UseItem((Box<dyn Item>, Target)),
+    /// indicates the usage of an item, holds the key of the item in the players inventory. it's
+    /// only taken out of the inventory once it is used.
+    UseItem((ItemKey, Target)),
+    /// indicates guarding, halves the damage taken until the entity's next turn
+    Defend,
+    /// indicates spending the turn getting ready for something, the str is shown to the player so
+    /// they can see it coming
+    Prepare(Arc<str>),
+    /// indicates calling the battles reserves in to fight
+    Summon,
 }
 
-// TODO: write an Ally trait
+/// what a spell (or other effect) does to its target.
+pub enum Effect {
+    /// hurts the target
+    Damage(Damage),
+    /// restores the targets health
+    Heal(u8),
+    /// puts a status effect on the target
+    Status(Box<dyn StatusEffect>),
+    /// brings a new ally into the battle on the casters side
+    Summon(Box<dyn Ally>),
+    /// gets the target out of the battle. only the player can leave, which ends it
+    Escape,
+}
+
+impl BattleAction {
+    /// the action to take on a second turn in the same round. only attacks are repeated.
+    fn repeat(&self) -> Option<BattleAction> {
+        match self {
+            BattleAction::Attack(attack) => Some(BattleAction::Attack(*attack)),
+            _ => None,
+        }
+    }
+}
+
+/// how a battle ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BattleOutcome {
+    /// every enemy was defeated.
+    Victory,
+    /// the player was defeated.
+    Defeat,
+    /// the player ran away.
+    Fled,
+}
+
+/// someone taking part in a battle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Actor {
+    Player,
+    Enemy(usize),
+    Ally(usize),
+}
+
+/// works out who acts when in a round, fastest first. `speeds` holds the speed of every
+/// combatant and the returned list holds indices into it. anyone at least twice as fast as the
+/// slowest combatant acts a second time at the end of the round. ties keep their original order.
+pub fn initiative(speeds: &[u8]) -> Vec<usize> {
+    let slowest = speeds.iter().copied().min().unwrap_or(0).max(1) as u16;
+    let mut order: Vec<usize> = (0..speeds.len()).collect();
+    order.sort_by_key(|i| std::cmp::Reverse(speeds[*i]));
+
+    let again: Vec<usize> = order
+        .iter()
+        .copied()
+        .filter(|i| speeds[*i] as u16 >= slowest * 2)
+        .collect();
+    order.extend(again);
+
+    order
+}
+
+/// the chance of fleeing at even speed.
+const FLEE_CHANCE: f32 = 0.5;
+/// the (lowest, highest) chance of fleeing, however fast or slow the player is.
+const FLEE_CHANCE_RANGE: (f32, f32) = (0.1, 0.95);
+
+/// the chance (0.0 to 1.0) of fleeing from a battle. scales with the ratio of the players speed
+/// to the speed of the fastest enemy, so twice as fast as the fastest enemy is a near sure thing.
+pub fn flee_chance(speed: u8, fastest: u8) -> f32 {
+    let ratio = speed.max(1) as f32 / fastest.max(1) as f32;
+
+    (FLEE_CHANCE * ratio).clamp(FLEE_CHANCE_RANGE.0, FLEE_CHANCE_RANGE.1)
+}
+
+/// a companion fighting on the players side. (hirelings, summoned creatures, etc) allies pick their
+/// own moves.
+// TODO: let other players characters join as allies for co-op. needs a way to send a Player
+// between nodes first.
+pub trait Ally: Battlable {
+    /// generates the move that the ally will take. targets are relative to the ally, see Target.
+    fn get_move(&mut self, state: &mut BattleState, player: &Player) -> BattleAction;
+
+    /// false for allies that only last for one battle. (summoned creatures, etc)
+    fn persists(&self) -> bool {
+        true
+    }
+}
 
 pub struct BattleState {
-    /// the player character
-    pub player: Player,
     /// the enemies the player is fighting
-    pub enemies: Arc<[Box<dyn Enemy>]>, // maybe make this a hashset for easy removing on enemy death
-    // allies: Arc<Box<dyn Ally>]>
+    pub enemies: Vec<Box<dyn Enemy>>, // maybe make this a hashset for easy removing on enemy death
+    /// the players allies. they join from the players party at the start of the battle and go
+    /// back to it at the end.
+    pub allies: Vec<Box<dyn Ally>>,
     /// stores if the battle has concluded
     pub over: bool,
     /// the number of turns elapst sinc ethe beginning of the battle
     pub turn_n: u16,
+    /// how the battle ended, None while it is still going.
+    pub outcome: Option<BattleOutcome>,
+    /// false if the player can't flee the battle. (boss fights, etc)
+    pub escapable: bool,
+    /// enemies waiting outside the battle until they are summoned. (a bosses minions, etc)
+    pub reserves: Vec<Box<dyn Enemy>>,
+    /// everything that has happened in the battle, one line per event.
+    pub log: Vec<String>,
+    /// xp earned from the enemies defeated so far. the player gets it when the battle ends.
+    pub xp: u32,
+    /// gold dropped by the enemies defeated so far.
+    pub gold: u32,
+    /// items dropped by the enemies defeated so far. once the battle is over, this holds
+    /// whatever the player had no room for.
+    pub spoils: Vec<ItemKey>,
+    /// true while the player is defending.
+    guarding: bool,
+    /// true once the players party has joined the battle.
+    joined: bool,
+    /// rolls hits, misses, and crits.
+    rng: StdRng,
+}
+
+impl BattleState {
+    pub fn new(enemies: Vec<Box<dyn Enemy>>) -> Self {
+        Self::with_seed(enemies, rand::thread_rng().gen())
+    }
+
+    /// makes a battle whose dice rolls are always the same. used to replay battles.
+    pub fn with_seed(enemies: Vec<Box<dyn Enemy>>, seed: u64) -> Self {
+        Self {
+            enemies,
+            allies: Vec::new(),
+            over: false,
+            turn_n: 0,
+            outcome: None,
+            escapable: true,
+            reserves: Vec::new(),
+            log: Vec::new(),
+            xp: 0,
+            gold: 0,
+            spoils: Vec::new(),
+            guarding: false,
+            joined: false,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// picks a random member of the players party for an enemy to go after.
+    pub fn pick_target(&mut self) -> Target {
+        Target::Enemy(self.rng.gen_range(0..=self.allies.len()))
+    }
+
+    /// the index of the living enemy with the least health, if any are left.
+    pub fn weakest_enemy(&self) -> Option<usize> {
+        (0..self.enemies.len())
+            .filter(|i| self.enemies[*i].is_alive())
+            .min_by_key(|i| self.enemies[*i].get_hp())
+    }
+
+    /// prints the combatants and their health.
+    fn show(&self, player: &Player) {
+        println!(
+            "{}: {}/{} hp, {}",
+            player.get_name(),
+            player.get_hp(),
+            player.get_max_hp(),
+            player.mana
+        );
+
+        for (i, ally) in self.allies.iter().enumerate() {
+            println!(
+                "  party {}) {}: {}/{} hp",
+                i + 2,
+                ally.get_name(),
+                ally.get_hp(),
+                ally.get_max_hp()
+            );
+        }
+
+        for (i, enemy) in self.enemies.iter().enumerate() {
+            println!(
+                "{}) {}: {}/{} hp",
+                i + 1,
+                enemy.get_name(),
+                enemy.get_hp(),
+                enemy.get_max_hp()
+            );
+        }
+    }
+
+    /// turns the players command into an action. returns None (after telling the player why) if
+    /// the command doesn't spend a turn.
+    fn parse(&self, cmd: &str, player: &mut Player) -> Option<BattleAction> {
+        let mut args = cmd.split_whitespace();
+
+        match args.next() {
+            Some("attack" | "a") => {
+                let target = args
+                    .next()
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .unwrap_or(1);
+
+                if target == 0 || target > self.enemies.len() {
+                    println!("there is no enemy {target}.");
+                    None
+                } else {
+                    Some(BattleAction::Attack((
+                        DamageType::Physical,
+                        Target::Enemy(target - 1),
+                    )))
+                }
+            }
+            Some("defend" | "guard" | "d") => Some(BattleAction::Defend),
+            // items can still get the player out of fights like this. (see Effect::Escape)
+            Some("flee" | "run") if !self.escapable => {
+                println!("there is no running from this fight!");
+                None
+            }
+            Some("flee" | "run") => Some(BattleAction::Flee),
+            Some("cast" | "c") => {
+                let mut words: Vec<&str> = args.collect();
+                let n = words.last().and_then(|n| n.parse::<usize>().ok());
+
+                if n.is_some() {
+                    words.pop();
+                }
+
+                let name = words.join(" ");
+
+                if player.spells.is_empty() {
+                    println!("you don't know any spells.");
+                    return None;
+                }
+
+                let Some(spell) = player.spell(&name) else {
+                    println!("you don't know a spell called \"{name}\". try spells");
+                    return None;
+                };
+
+                if let Err(e) = player.mana.check(spell) {
+                    println!("{e}");
+                    return None;
+                }
+
+                let target = self.choose_target(spell.target_mode(), n)?;
+
+                spells::learn(&spell.name()).map(|spell| BattleAction::CastSpell((spell, target)))
+            }
+            Some("spells") => {
+                if player.spells.is_empty() {
+                    println!("you don't know any spells.");
+                }
+
+                for spell in &player.spells {
+                    match player.mana.cooldown(&spell.name()) {
+                        0 => println!("{}", spells::describe(spell.as_ref())),
+                        n => println!("{} (ready in {n})", spells::describe(spell.as_ref())),
+                    }
+                }
+
+                None
+            }
+            Some("use" | "u") => {
+                let mut words: Vec<&str> = args.collect();
+                let n = words.last().and_then(|n| n.parse::<usize>().ok());
+
+                if n.is_some() {
+                    words.pop();
+                }
+
+                let name = words.join(" ");
+
+                let Some(key) = player.inventory.find(&name) else {
+                    println!("you don't have a \"{name}\". try inventory");
+                    return None;
+                };
+
+                let item = items::make(&key)?;
+
+                let notes =
+                    matches!(items::lookup(&key.name), Some(def) if def.does == Use::Recipe);
+
+                if item.category() != Category::Consumable || notes {
+                    println!("you can't use the {} in battle.", item.name());
+                    return None;
+                }
+
+                let target = self.choose_target(item.target_mode(), n)?;
+
+                Some(BattleAction::UseItem((key, target)))
+            }
+            Some("inventory" | "inv" | "i") => {
+                for line in player.inventory.listing() {
+                    println!("{line}");
+                }
+
+                None
+            }
+            Some("look" | "status") => {
+                self.show(player);
+                None
+            }
+            Some("stats") => {
+                for line in player.stat_sheet() {
+                    println!("{line}");
+                }
+
+                None
+            }
+            Some(cmd) => {
+                println!(
+                    "unknown command \"{cmd}\". try attack <n>, cast <spell> <n>, use <item> <n>, defend, flee, or status"
+                );
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// works out the target for a spell or item from the number the player gave, if they gave
+    /// one. enemies are numbered from 1, and so is the players party. (the player is 1)
+    fn choose_target(&self, mode: TargetMode, n: Option<usize>) -> Option<Target> {
+        let target = match (mode, n.unwrap_or(1)) {
+            (TargetMode::Single, n) if n == 0 || n > self.enemies.len() => {
+                println!("there is no enemy {n}.");
+                return None;
+            }
+            (TargetMode::Single | TargetMode::All, n) => Target::Enemy(n.max(1) - 1),
+            (TargetMode::Ally, n) if n > self.allies.len() + 1 => {
+                println!("there is no party member {n}.");
+                return None;
+            }
+            // party member 1 is the player.
+            (TargetMode::Ally, n) => Target::Ally(n.max(1) - 1),
+            (TargetMode::TheSelf, _) => Target::TheSelf,
+        };
+
+        Some(target)
+    }
+
+    fn name_of(&self, who: Actor, player: &Player) -> Arc<str> {
+        match who {
+            Actor::Player => player.get_name(),
+            Actor::Enemy(i) => self.enemies[i].get_name(),
+            Actor::Ally(i) => self.allies[i].get_name(),
+        }
+    }
+
+    fn stats_of(&self, who: Actor, player: &Player) -> Stats {
+        match who {
+            Actor::Player => player.get_stats(),
+            Actor::Enemy(i) => self.enemies[i].get_stats(),
+            Actor::Ally(i) => self.allies[i].get_stats(),
+        }
+    }
+
+    /// deals damage to a combatant and logs it. returns whether they survived.
+    fn hurt(&mut self, who: Actor, mut dmg: Damage, player: &mut Player) -> Lifeness {
+        let name = self.name_of(who, player);
+
+        if who == Actor::Player && self.guarding {
+            dmg.amount /= 2;
+            self.log.push(format!("{name} braces for the blow."));
+        }
+
+        let life = match who {
+            Actor::Player => player.apply_damage(dmg),
+            Actor::Enemy(i) => self.enemies[i].apply_damage(dmg),
+            Actor::Ally(i) => self.allies[i].apply_damage(dmg),
+        };
+
+        if dmg.crit {
+            self.log.push("a critical hit!".to_string());
+        }
+
+        self.log
+            .push(format!("{name} takes {} {} damage.", dmg.amount, dmg.kind));
+
+        if let Lifeness::Dead = life {
+            self.log.push(format!("{name} is defeated!"));
+        }
+
+        life
+    }
+
+    /// the member of the players party at `i`. the player is 0, their allies follow. falls back
+    /// to the player if that ally is gone.
+    fn party_member(&self, i: usize, fallen: &[Actor]) -> Actor {
+        match i {
+            i if i > 0 && i <= self.allies.len() && !fallen.contains(&Actor::Ally(i - 1)) => {
+                Actor::Ally(i - 1)
+            }
+            _ => Actor::Player,
+        }
+    }
+
+    /// works out who an action is aimed at. returns None if there is no one left to target.
+    fn aim(&self, actor: Actor, target: Target, fallen: &[Actor]) -> Option<Actor> {
+        match (actor, target) {
+            (_, Target::TheSelf) => Some(actor),
+            // the chosen enemy may have fallen earlier in the round, pick another.
+            (Actor::Player | Actor::Ally(_), Target::Enemy(i)) => (0..self.enemies.len())
+                .cycle()
+                .skip(i)
+                .take(self.enemies.len())
+                .find(|i| !fallen.contains(&Actor::Enemy(*i)))
+                .map(Actor::Enemy),
+            (Actor::Player | Actor::Ally(_), Target::Ally(i)) => Some(self.party_member(i, fallen)),
+            (Actor::Enemy(_), Target::Enemy(i)) => Some(self.party_member(i, fallen)),
+            (Actor::Enemy(_), Target::Ally(i))
+                if i < self.enemies.len() && !fallen.contains(&Actor::Enemy(i)) =>
+            {
+                Some(Actor::Enemy(i))
+            }
+            (Actor::Enemy(_), Target::Ally(_)) => Some(actor),
+        }
+    }
+
+    /// everyone a spell or item with the given target mode lands on.
+    fn targets(
+        &self,
+        actor: Actor,
+        mode: TargetMode,
+        target: Target,
+        fallen: &[Actor],
+    ) -> Vec<Actor> {
+        match mode {
+            TargetMode::TheSelf => vec![actor],
+            TargetMode::All => self.opponents(actor, fallen),
+            TargetMode::Single | TargetMode::Ally => {
+                self.aim(actor, target, fallen).into_iter().collect()
+            }
+        }
+    }
+
+    /// logs someone casting a spell or using an item. (`verb` is "casts", "uses", etc)
+    fn announce(
+        &mut self,
+        actor: Actor,
+        verb: &str,
+        what: &str,
+        targets: &[Actor],
+        player: &Player,
+    ) {
+        let name = self.name_of(actor, player);
+
+        match targets {
+            [target] if *target != actor => self.log.push(format!(
+                "{name} {verb} {what} on {}.",
+                self.name_of(*target, player)
+            )),
+            _ => self.log.push(format!("{name} {verb} {what}.")),
+        }
+    }
+
+    /// everyone on the other side of the battle from `actor` who is still standing.
+    fn opponents(&self, actor: Actor, fallen: &[Actor]) -> Vec<Actor> {
+        let side = match actor {
+            Actor::Player | Actor::Ally(_) => (0..self.enemies.len()).map(Actor::Enemy).collect(),
+            Actor::Enemy(_) => std::iter::once(Actor::Player)
+                .chain((0..self.allies.len()).map(Actor::Ally))
+                .collect::<Vec<_>>(),
+        };
+
+        side.into_iter()
+            .filter(|who| !fallen.contains(who))
+            .collect()
+    }
+
+    /// applies an effect to a combatant and logs it.
+    fn affect(&mut self, who: Actor, effect: Effect, player: &mut Player, fallen: &mut Vec<Actor>) {
+        let name = self.name_of(who, player);
+
+        match effect {
+            Effect::Damage(dmg) => {
+                if let Lifeness::Dead = self.hurt(who, dmg, player) {
+                    if who != Actor::Player {
+                        fallen.push(who);
+                    }
+                }
+            }
+            Effect::Heal(amount) => {
+                let restored = match who {
+                    Actor::Player => player.heal(amount),
+                    Actor::Enemy(i) => self.enemies[i].heal(amount),
+                    Actor::Ally(i) => self.allies[i].heal(amount),
+                };
+
+                self.log.push(format!("{name} recovers {restored} hp."));
+            }
+            Effect::Status(effect) => {
+                self.log.push(format!(
+                    "{name} is now affected by {}.",
+                    effect.display_name()
+                ));
+
+                match who {
+                    Actor::Player => player.add_status(effect),
+                    Actor::Enemy(i) => self.enemies[i].add_status(effect),
+                    Actor::Ally(i) => self.allies[i].add_status(effect),
+                }
+            }
+            Effect::Summon(ally) => match who {
+                Actor::Player | Actor::Ally(_) => {
+                    self.log
+                        .push(format!("{} joins the battle!", ally.get_name()));
+                    self.allies.push(ally);
+                }
+                Actor::Enemy(_) => self.log.push("but nothing answers the call.".to_string()),
+            },
+            Effect::Escape => match who {
+                Actor::Player => {
+                    self.log
+                        .push(format!("{name} vanishes in a cloud of smoke!"));
+                    self.outcome = Some(BattleOutcome::Fled);
+                }
+                _ => self.log.push(format!("{name} has nowhere to go.")),
+            },
+        }
+    }
+
+    /// carries out an action. `fallen` holds the combatants that have been defeated this round,
+    /// they can't act or be targeted.
+    fn resolve(
+        &mut self,
+        actor: Actor,
+        action: BattleAction,
+        player: &mut Player,
+        fallen: &mut Vec<Actor>,
+    ) {
+        let name = self.name_of(actor, player);
+
+        if actor == Actor::Player {
+            self.guarding = false;
+        }
+
+        match action {
+            BattleAction::Attack((kind, target)) => {
+                let Some(target) = self.aim(actor, target, fallen) else {
+                    return;
+                };
+
+                let attack = format!("{name} attacks {}", self.name_of(target, player));
+                let attacker = self.stats_of(actor, player);
+                let defender = self.stats_of(target, player);
+                let accuracy = match actor {
+                    Actor::Player => player.accuracy(),
+                    Actor::Enemy(i) => self.enemies[i].accuracy(),
+                    Actor::Ally(i) => self.allies[i].accuracy(),
+                };
+
+                let dmg = match self.rng.gen::<f32>() < accuracy {
+                    true => damage::roll(&attacker, &defender, kind, &mut self.rng),
+                    false => None,
+                };
+
+                let Some(dmg) = dmg else {
+                    self.log.push(format!("{attack} but misses."));
+                    return;
+                };
+
+                self.log.push(format!("{attack}."));
+                self.affect(target, Effect::Damage(dmg), player, fallen);
+            }
+            BattleAction::CastSpell((mut spell, target)) => {
+                let mana = match actor {
+                    Actor::Player => Some(&mut player.mana),
+                    Actor::Enemy(i) => self.enemies[i].mana(),
+                    Actor::Ally(i) => self.allies[i].mana(),
+                };
+
+                // anyone without mana casts as they please.
+                if let Some(Err(e)) = mana.map(|mana| mana.spend(spell.as_ref())) {
+                    self.log.push(format!("{name}'s spell fizzles. {e}"));
+                    return;
+                }
+
+                let targets = self.targets(actor, spell.target_mode(), target, fallen);
+                self.announce(actor, "casts", &spell.name(), &targets, player);
+
+                let caster = self.stats_of(actor, player);
+
+                for target in targets {
+                    let effect = spell.cast(&caster, &self.stats_of(target, player));
+                    self.affect(target, effect, player, fallen);
+                }
+            }
+            // the player tries to flee at the start of the round (see round), no one else runs.
+            BattleAction::Flee => {}
+            BattleAction::UseItem((key, target)) => {
+                let Some(mut item) = player.inventory.take(&key) else {
+                    self.log.push(format!(
+                        "{name} reaches for the {} but it's gone.",
+                        key.name
+                    ));
+                    return;
+                };
+
+                let targets = self.targets(actor, item.target_mode(), target, fallen);
+                self.announce(actor, "uses", &item.name(), &targets, player);
+
+                let user = self.stats_of(actor, player);
+
+                for target in targets {
+                    match item.use_on(&user, &self.stats_of(target, player)) {
+                        Some(effect) => self.affect(target, effect, player, fallen),
+                        None => self.log.push("nothing happens.".to_string()),
+                    }
+                }
+            }
+            BattleAction::Defend => {
+                self.log.push(format!("{name} takes a defensive stance."));
+                self.guarding = actor == Actor::Player;
+            }
+            BattleAction::Prepare(msg) => self.log.push(msg.to_string()),
+            BattleAction::Summon => {
+                for enemy in self.reserves.drain(..) {
+                    self.log
+                        .push(format!("{} joins the battle!", enemy.get_name()));
+                    self.enemies.push(enemy);
+                }
+            }
+        }
+    }
+
+    /// ticks the status effects on a combatant at the start of their turn. returns false if they
+    /// can't act this turn. (stunned, finished off by poison, etc)
+    fn upkeep(&mut self, who: Actor, player: &mut Player, fallen: &mut Vec<Actor>) -> bool {
+        let name = self.name_of(who, player);
+        let ticks = match who {
+            Actor::Player => status::tick_all(&mut player.status),
+            Actor::Enemy(i) => status::tick_all(self.enemies[i].status_effects()),
+            Actor::Ally(i) => status::tick_all(self.allies[i].status_effects()),
+        };
+        let mut can_act = true;
+
+        for (effect, tick, worn_off) in ticks {
+            if tick.damage > 0 {
+                self.log.push(format!("{name} suffers from {effect}."));
+                let dmg = Damage {
+                    amount: tick.damage,
+                    kind: DamageType::Magical,
+                    crit: false,
+                };
+                self.affect(who, Effect::Damage(dmg), player, fallen);
+            }
+
+            if tick.heal > 0 {
+                self.affect(who, Effect::Heal(tick.heal), player, fallen);
+            }
+
+            if tick.skip_turn {
+                self.log
+                    .push(format!("{name} can't move because of {effect}!"));
+                can_act = false;
+            }
+
+            if worn_off {
+                self.log.push(format!("{name}'s {effect} wears off."));
+            }
+        }
+
+        match who {
+            Actor::Player => can_act && player.is_alive(),
+            _ => can_act && !fallen.contains(&who),
+        }
+    }
+
+    /// rolls for the player to escape. see flee_chance.
+    fn flee(&mut self, player: &Player) -> bool {
+        let fastest = self
+            .enemies
+            .iter()
+            .map(|enemy| enemy.get_stats().speed)
+            .max()
+            .unwrap_or(0);
+
+        if self.rng.gen::<f32>() < flee_chance(player.get_stats().speed, fastest) {
+            self.log.push(format!("{} flees!", player.get_name()));
+            self.outcome = Some(BattleOutcome::Fled);
+            true
+        } else {
+            self.log.push(format!(
+                "{} tries to flee but can't get away!",
+                player.get_name()
+            ));
+            false
+        }
+    }
+
+    /// hands out the xp, gold, and items won in the battle, and logs a summary of them. items
+    /// there is no room for are left in `spoils`.
+    fn reward(&mut self, player: &mut Player) {
+        if self.xp == 0 && self.gold == 0 && self.spoils.is_empty() {
+            return;
+        }
+
+        self.log.push("-- rewards --".to_string());
+
+        if self.xp > 0 {
+            let lines = player.gain_xp(std::mem::take(&mut self.xp));
+            self.log.extend(lines);
+        }
+
+        if self.gold > 0 {
+            player.gold += self.gold;
+            self.log.push(format!(
+                "{} finds {} gold. ({} total)",
+                player.get_name(),
+                std::mem::take(&mut self.gold),
+                player.gold
+            ));
+        }
+
+        let mut left = Vec::new();
+
+        for key in self.spoils.drain(..) {
+            let Some(item) = items::make(&key) else {
+                continue;
+            };
+
+            match player.inventory.add(item.as_ref()) {
+                Ok(_) => self.log.push(format!(
+                    "{} picks up the {}.",
+                    player.get_name(),
+                    item.name()
+                )),
+                Err(_) => {
+                    self.log.push(format!(
+                        "there is no room for the {}, it's left behind.",
+                        item.name()
+                    ));
+                    left.push(key);
+                }
+            }
+        }
+
+        self.spoils = left;
+    }
+
+    /// plays one round of the battle. the player uses `action` on their turn, and repeats it if
+    /// they get a second one.
+    fn round(&mut self, action: BattleAction, player: &mut Player) {
+        let speeds: Vec<u8> = std::iter::once(player.get_stats().speed)
+            .chain(self.enemies.iter().map(|enemy| enemy.get_stats().speed))
+            .chain(self.allies.iter().map(|ally| ally.get_stats().speed))
+            .collect();
+        // anyone who joins part way through the round waits for the next one.
+        let n_enemies = self.enemies.len();
+        let mut fallen = Vec::new();
+        let mut action = Some(action);
+
+        self.turn_n += 1;
+        self.log.push(format!("-- turn {} --", self.turn_n));
+
+        // running away is tried before anyone else moves. a failed attempt uses up the players
+        // turn, giving the enemies a free one.
+        if matches!(action, Some(BattleAction::Flee)) {
+            action = None;
+
+            if self.upkeep(Actor::Player, player, &mut fallen) && self.flee(player) {
+                return;
+            }
+        }
+
+        for i in initiative(&speeds) {
+            if self.outcome.is_some() || !player.is_alive() {
+                break;
+            }
+
+            let actor = match i {
+                0 => Actor::Player,
+                i if i <= n_enemies => Actor::Enemy(i - 1),
+                i => Actor::Ally(i - 1 - n_enemies),
+            };
+
+            match actor {
+                Actor::Player => {
+                    if let Some(now) = action.take() {
+                        action = now.repeat();
+
+                        if self.upkeep(Actor::Player, player, &mut fallen) {
+                            self.resolve(Actor::Player, now, player, &mut fallen);
+                        }
+                    }
+                }
+                _ if fallen.contains(&actor) => {}
+                _ if !self.upkeep(actor, player, &mut fallen) => {}
+                // the enemy is taken out of the battle while it decides what to do, so it can
+                // look at the rest of the battle.
+                Actor::Enemy(i) => {
+                    let mut enemy = self.enemies.remove(i);
+                    let action = enemy.get_move(self);
+                    self.enemies.insert(i, enemy);
+                    self.resolve(actor, action, player, &mut fallen);
+                }
+                Actor::Ally(i) => {
+                    let mut ally = self.allies.remove(i);
+                    let action = ally.get_move(self, player);
+                    self.allies.insert(i, ally);
+                    self.resolve(actor, action, player, &mut fallen);
+                }
+            }
+        }
+
+        player.mana.tick();
+
+        for enemy in self.enemies.iter_mut() {
+            if let Some(mana) = enemy.mana() {
+                mana.tick();
+            }
+        }
+
+        for ally in self.allies.iter_mut() {
+            if let Some(mana) = ally.mana() {
+                mana.tick();
+            }
+        }
+
+        fallen.sort_unstable_by_key(|who| std::cmp::Reverse(*who));
+
+        for who in fallen {
+            match who {
+                Actor::Enemy(i) => {
+                    let enemy = self.enemies.remove(i);
+                    let (gold, items) = enemy.drops(&mut self.rng);
+
+                    self.xp += experience::reward(&enemy.get_stats());
+                    player.card.defeat(&enemy.get_stats(), &player.get_stats());
+                    self.gold += gold;
+                    self.spoils.extend(items);
+                }
+                Actor::Ally(i) => drop(self.allies.remove(i)),
+                Actor::Player => {}
+            }
+        }
+
+        if self.outcome.is_none() {
+            if !player.is_alive() {
+                self.log.push("you have been defeated...".to_string());
+                self.outcome = Some(BattleOutcome::Defeat);
+            } else if self.enemies.is_empty() {
+                self.log.push("victory!".to_string());
+                self.outcome = Some(BattleOutcome::Victory);
+                player.card.won(self.turn_n);
+            }
+        }
+    }
 }
 
 impl GameState for BattleState {
     #[allow(unused_variables)]
-    fn step(&mut self, cmd: &str, player: &Player) -> bool {
-        // TODO: implement battle logic and battle command parsing
-        self.over = self.over && !self.enemies.is_empty(); // should be idempotent
+    fn step(&mut self, cmd: &str, player: &mut Player, world: &mut World) -> bool {
+        if self.over {
+            return true;
+        }
+
+        if !self.joined {
+            self.joined = true;
+            self.allies.append(&mut player.party);
+        }
+
+        let Some(action) = self.parse(cmd, player) else {
+            return false;
+        };
+
+        let seen = self.log.len();
+        self.round(action, player);
+
+        // fleeing keeps the rewards from anything defeated before running.
+        if self.outcome.is_some() && player.is_alive() {
+            self.reward(player);
+        }
+
+        for line in &self.log[seen..] {
+            println!("{line}");
+        }
+
+        self.over = self.outcome.is_some();
+
+        // the party goes back to the player, minus anyone who was only here for the fight.
+        if self.over {
+            player
+                .party
+                .extend(self.allies.drain(..).filter(|ally| ally.persists()));
+        }
 
         self.is_done()
     }
@@ -52,11 +942,153 @@ impl GameState for BattleState {
 }
 
 pub trait Spell {
-    fn new() -> Self
-    where
-        Self: Sized;
-    fn cast(&mut self, state: &mut BattleState);
+    /// returns the name of the spell. (eg, "Fireball", "Thorn Lash", etc)
+    fn name(&self) -> Arc<str>;
+
+    /// the element the spell draws on.
+    fn school(&self) -> School {
+        School::Arcane
+    }
+
+    /// who the spell can be cast on.
+    fn target_mode(&self) -> TargetMode {
+        TargetMode::Single
+    }
+
+    /// how much mana it takes to cast the spell.
+    fn cost(&self) -> u8 {
+        0
+    }
+
+    /// how many rounds the caster must wait before casting the spell again.
+    fn cooldown(&self) -> u8 {
+        0
+    }
+
+    /// works out what the spell does to a single target. spells that hit more than one target
+    /// are cast once per target.
+    fn cast(&mut self, caster: &Stats, target: &Stats) -> Effect;
+}
+
+/// represents entities that can enter a battle. (the player, enemies, etc) lets the battle code
+/// treat everyone the same.
+pub trait Battlable {
+    /// returns the name to show in battle.
+    fn get_name(&self) -> Arc<str>;
+
+    /// returns the entities current health.
+    fn get_hp(&self) -> u8;
+
+    /// returns the entities max health.
+    fn get_max_hp(&self) -> u8 {
+        self.get_stats().hp
+    }
+
+    /// returns the entities stats after gear, buffs, and status effects have been applied.
+    fn get_stats(&self) -> Stats;
+
+    /// applies damage to the entity.
+    fn apply_damage(&mut self, dmg: Damage) -> Lifeness;
+
+    /// restores up to `amount` health without going over max health. returns how much health was
+    /// actually restored.
+    fn heal(&mut self, amount: u8) -> u8;
+
+    /// the status effects affecting the entity.
+    fn status_effects(&mut self) -> &mut Vec<Box<dyn StatusEffect>>;
+
+    /// the entity's mana, None for things that don't need mana to cast spells.
+    fn mana(&mut self) -> Option<&mut Mana> {
+        None
+    }
+
+    /// puts a new status effect on the entity. if the entity already has an effect of the same
+    /// name they are stacked according to the effects stacking rule.
+    fn add_status(&mut self, effect: Box<dyn StatusEffect>) {
+        let name = effect.display_name();
+        let same = self
+            .status_effects()
+            .iter_mut()
+            .find(|existing| existing.display_name() == name);
+
+        match same {
+            Some(existing) if effect.stacking() != Stacking::Separate => {
+                existing.merge(effect.turns(), effect.potency())
+            }
+            _ => self.status_effects().push(effect),
+        }
+    }
+
+    /// the chance (0.0 to 1.0) that the entity's attacks land at all, before speed is taken into
+    /// account.
+    fn accuracy(&mut self) -> f32 {
+        self.status_effects()
+            .iter()
+            .map(|effect| effect.accuracy())
+            .product()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.get_hp() > 0
+    }
 }
 
-// TODO: write a Battlable trait to represnet entities that can enter a battle.
-pub trait Battlable {}
+/// the health left after healing `amount`, and how much was restored. shared by implementors of
+/// Battlable::heal.
+pub fn healed(hp: u8, max_hp: u8, amount: u8) -> (u8, u8) {
+    let new_hp = hp.saturating_add(amount).min(max_hp).max(hp);
+
+    (new_hp, new_hp - hp)
+}
+
+/// turns health into Lifeness. shared by implementors of Battlable::apply_damage.
+pub fn lifeness(hp: u8) -> Lifeness {
+    match hp {
+        0 => Lifeness::Dead,
+        _ => Lifeness::Alive,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initiative_table() {
+        // (speeds, turn order)
+        let table: [(&[u8], &[usize]); 6] = [
+            (&[], &[]),
+            (&[4], &[0]),
+            (&[3, 3, 3], &[0, 1, 2]),
+            (&[2, 5], &[1, 0, 1]),
+            (&[4, 2, 8], &[2, 0, 1, 2, 0]),
+            // no one is slower than 1, so anyone at 2 or more goes twice.
+            (&[0, 5], &[1, 0, 1]),
+        ];
+
+        for (speeds, order) in table {
+            assert_eq!(initiative(speeds), order, "initiative({speeds:?})");
+        }
+    }
+
+    #[test]
+    fn flee_chance_table() {
+        // (player speed, fastest enemy speed, chance)
+        let table = [
+            (4, 4, 0.5),
+            (6, 4, 0.75),
+            (2, 4, 0.25),
+            (8, 4, 0.95),
+            (1, 20, 0.1),
+            (0, 0, 0.5),
+        ];
+
+        for (speed, fastest, chance) in table {
+            let got = flee_chance(speed, fastest);
+            assert!(
+                (got - chance).abs() < 1e-6,
+                "flee_chance({speed}, {fastest}) = {got}"
+            );
+        }
+    }
+}
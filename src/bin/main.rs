@@ -4,17 +4,33 @@ use esp_idf_hal::gpio::AnyIOPin;
 use esp_idf_hal::i2c::*;
 use esp_idf_hal::prelude::*;
 use esp_idf_hal::{i2c::I2cDriver, peripherals};
+use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs, NvsDefault};
 use esp_idf_sys as _; // If using the `binstart` feature of `esp-idf-sys`, always keep this module imported
 use esp_idf_sys::{esp, esp_vfs_dev_uart_use_driver, uart_driver_install};
 use log::*;
 use rogue_xork::biome;
+use rogue_xork::fame::HallOfFame;
 use rogue_xork::level::Biome;
-use rogue_xork::{game::Game, I2cAdr, I2C_SPEED};
+use rogue_xork::{game::Game, serve, swap_fame, worker_address, I2cAdr, I2cCom, I2C_SPEED, PING};
 use std::io::stdin;
 use std::ptr::null_mut;
+use std::sync::{Arc, Mutex};
+use std::thread;
 
 // pub static I2C_ADDRESS: u8 = 0;
 
+/// the nvs namespace the game keeps its data under.
+const NVS_NAMESPACE: &str = "rogue_xork";
+/// the nvs key the hall of fame is saved under.
+const FAME_KEY: &str = "hall_of_fame";
+/// the most bytes the saved hall of fame can take up.
+const FAME_SIZE: usize = 8192;
+/// the stack size of the thread answering other nodes. decoding a hall of fame needs more than
+/// the default.
+const WORKER_STACK: usize = 16 * 1024;
+/// how many bytes the i2c driver buffers for requests coming in and replies going out.
+const WORKER_BUFFER: usize = 1024;
+
 fn main() -> anyhow::Result<()> {
     // It is necessary to call this function once. Otherwise some patches to the runtime
     // implemented by esp-idf-sys might not link properly. See https://github.com/esp-rs/esp-idf-template/issues/71
@@ -50,10 +66,43 @@ fn main() -> anyhow::Result<()> {
         &i2c_conf,
     )?;
 
-    let mut game = void_setup()?;
+    // other nodes send their requests to this one on a second i2c port.
+    let slave_conf = I2cSlaveConfig::new()
+        .rx_buffer_length(WORKER_BUFFER)
+        .tx_buffer_length(WORKER_BUFFER);
+    let mut slave = I2cSlaveDriver::new(
+        peripherals.i2c1,
+        Into::<AnyIOPin>::into(pins.gpio18),
+        Into::<AnyIOPin>::into(pins.gpio19),
+        worker_address(biome!()),
+        &slave_conf,
+    )?;
+    info!(
+        "answering requests on address {}.",
+        worker_address(biome!())
+    );
+
+    info!("opening nvs...");
+    let mut nvs = EspNvs::new(EspDefaultNvsPartition::take()?, NVS_NAMESPACE, true)?;
+    info!("nvs opened.");
+
+    let mut game = void_setup(&mut i2c, &mut nvs)?;
+
+    // other nodes can swap halls of fame with this one at any time, so the worker side gets its
+    // own thread and its own copy of the hall. the two are merged every loop. (see void_loop)
+    let fame = Arc::new(Mutex::new(game.world.fame.clone()));
+    let worker_fame = fame.clone();
+
+    thread::Builder::new()
+        .stack_size(WORKER_STACK)
+        .spawn(move || loop {
+            if let Err(why) = serve(&mut slave, &worker_fame) {
+                error!("could not answer a request: {why}");
+            }
+        })?;
 
     loop {
-        if let Err(why) = void_loop(&mut i2c, &mut game) {
+        if let Err(why) = void_loop(&mut i2c, &mut nvs, &mut game, &fame) {
             error!("a loop iteration failed because: {why}");
         }
 
@@ -61,15 +110,29 @@ fn main() -> anyhow::Result<()> {
     }
 }
 
-fn void_setup() -> anyhow::Result<Game> {
-    let mut game = Game::new();
-    game.world.generate()?;
+fn void_setup(i2c: &mut I2cDriver, nvs: &mut EspNvs<NvsDefault>) -> anyhow::Result<Game> {
+    let mut game = Game::new()?;
     info!("world created");
 
+    game.world.fame = load_fame(nvs).unwrap_or_else(|why| {
+        error!("could not load the hall of fame: {why}");
+        HallOfFame::new()
+    });
+    share_fame(i2c, &mut game.world.fame);
+
+    if game.world.fame.take_changed() {
+        save_fame(nvs, &game.world.fame)?;
+    }
+
     Ok(game)
 }
 
-fn void_loop(i2c: &mut I2cDriver, game: &mut Game) -> anyhow::Result<()> {
+fn void_loop(
+    i2c: &mut I2cDriver,
+    nvs: &mut EspNvs<NvsDefault>,
+    game: &mut Game,
+    fame: &Mutex<HallOfFame>,
+) -> anyhow::Result<()> {
     // TODO: get player input
     print!("~ >>> ");
     let mut buffer = String::new();
@@ -79,11 +142,75 @@ fn void_loop(i2c: &mut I2cDriver, game: &mut Game) -> anyhow::Result<()> {
         Err(e) => bail!("reading from UART in failed with error: \"{e}\""),
     }
 
-    // TODO: do player input
+    game.step(buffer.trim());
+
+    // catch up with the worker thread. halls swapped in by other nodes come into the game, and
+    // victories on this node go out to be swapped.
+    {
+        let Ok(mut shared) = fame.lock() else {
+            bail!("the shared hall of fame was poisoned by a panic");
+        };
+
+        game.world.fame.merge(shared.clone());
+        shared.merge(game.world.fame.clone());
+    }
+
+    // someone new made it into the hall of fame, let every other node know.
+    if game.world.fame.take_changed() {
+        share_fame(i2c, &mut game.world.fame);
+        game.world.fame.take_changed();
+        save_fame(nvs, &game.world.fame)?;
+    }
 
     Ok(())
 }
 
+/// reads the hall of fame out of nvs. a device that has never saved one starts with it empty.
+fn load_fame(nvs: &mut EspNvs<NvsDefault>) -> anyhow::Result<HallOfFame> {
+    let mut buf = vec![0; FAME_SIZE];
+    let fame = HallOfFame::new();
+
+    match nvs.get_raw(FAME_KEY, &mut buf)? {
+        Some(data) => fame.decode_from(data),
+        None => Ok(fame),
+    }
+}
+
+/// writes the hall of fame to nvs.
+fn save_fame(nvs: &mut EspNvs<NvsDefault>, fame: &HallOfFame) -> anyhow::Result<()> {
+    let data = fame.encode()?;
+
+    if data.len() > FAME_SIZE {
+        bail!("the hall of fame is too big to save ({} bytes)", data.len());
+    }
+
+    nvs.set_raw(FAME_KEY, &data)?;
+    info!("hall of fame saved.");
+
+    Ok(())
+}
+
+/// swaps halls of fame with every node on the bus, so each board remembers everyone it has met.
+fn share_fame(i2c: &mut I2cDriver, fame: &mut HallOfFame) {
+    let nodes = match i2c_scan(i2c) {
+        Ok(nodes) => nodes,
+        Err(why) => {
+            error!("could not scan for nodes: {why}");
+            return;
+        }
+    };
+
+    // this nodes own worker answers on the bus too, but it can't answer while it's asking.
+    for node in nodes
+        .into_iter()
+        .filter(|node| *node != worker_address(biome!()))
+    {
+        if let Err(why) = swap_fame(i2c, node, fame) {
+            error!("could not swap halls of fame with node {node}: {why}");
+        }
+    }
+}
+
 /// scans all i2c addresses for worker nodes.
 fn i2c_scan(i2c: &mut I2cDriver) -> anyhow::Result<Vec<I2cAdr>> {
     Ok((0..127)
@@ -93,5 +220,5 @@ fn i2c_scan(i2c: &mut I2cDriver) -> anyhow::Result<Vec<I2cAdr>> {
 }
 
 fn is_host(adr: I2cAdr, i2c: &mut I2cDriver) -> bool {
-    i2c.write(adr, &[3], 10).is_ok()
+    i2c.write(adr, &[PING], 10).is_ok()
 }
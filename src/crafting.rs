@@ -0,0 +1,181 @@
+use crate::inventory::Inventory;
+use crate::items::{self, ItemKey};
+use crate::zone::ZoneType;
+use anyhow::{bail, Result};
+use lazy_static::lazy_static;
+use rand::prelude::*;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// something the player can make. recipes are loaded from recipes.json, so a new recipe only
+/// needs a new entry there (as long as every item it names is in the item catalog).
+#[derive(Debug, Deserialize)]
+pub struct Recipe {
+    /// the name of the item it makes.
+    pub makes: String,
+    /// how many it makes.
+    #[serde(default = "one")]
+    pub count: u8,
+    /// what goes into it, as (item, count). it's all used up.
+    pub needs: Vec<(String, u8)>,
+    /// true if the player knows the recipe from the start. everything else has to be discovered.
+    #[serde(default)]
+    pub known: bool,
+}
+
+fn one() -> u8 {
+    1
+}
+
+lazy_static! {
+    static ref RECIPES: Vec<Recipe> = serde_json::from_str(include_str!("recipes.json"))
+        .expect("recipes.json should be a list of recipes");
+}
+
+/// every recipe in the game.
+pub fn recipes() -> &'static [Recipe] {
+    &RECIPES
+}
+
+/// finds a recipe by the name of what it makes, ignoring case.
+pub fn recipe(name: &str) -> Option<&'static Recipe> {
+    RECIPES
+        .iter()
+        .find(|recipe| recipe.makes.eq_ignore_ascii_case(name))
+}
+
+/// the recipes every player starts out knowing.
+pub fn starting() -> Vec<Arc<str>> {
+    RECIPES
+        .iter()
+        .filter(|recipe| recipe.known)
+        .map(|recipe| Arc::from(recipe.makes.as_str()))
+        .collect()
+}
+
+/// true if the player can craft in this kind of zone. crafting needs somewhere safe to work.
+pub fn workshop(kind: &ZoneType) -> bool {
+    matches!(kind, ZoneType::Shelter | ZoneType::Sanctuary)
+}
+
+/// how many of an item are being carried, counting every instance of it.
+fn have(inventory: &Inventory, name: &str) -> u8 {
+    inventory
+        .sorted()
+        .iter()
+        .filter(|(key, _, _)| key.name.eq_ignore_ascii_case(name))
+        .fold(0, |total, (_, count, _)| total.saturating_add(*count))
+}
+
+impl Recipe {
+    /// the ingredients the player is short on, as "n name".
+    pub fn missing(&self, inventory: &Inventory) -> Vec<String> {
+        self.needs
+            .iter()
+            .filter_map(|(name, n)| {
+                let have = have(inventory, name);
+                (have < *n).then(|| format!("{} {name}", n - have))
+            })
+            .collect()
+    }
+
+    /// the recipe as the player sees it.
+    pub fn describe(&self, inventory: &Inventory) -> String {
+        let needs: Vec<String> = self
+            .needs
+            .iter()
+            .map(|(name, n)| format!("{n} {name} ({})", have(inventory, name)))
+            .collect();
+        let ready = match self.missing(inventory).is_empty() {
+            true => " - ready",
+            false => "",
+        };
+
+        match self.count {
+            1 => format!("{}: {}{ready}", self.makes, needs.join(", ")),
+            n => format!("{} x{n}: {}{ready}", self.makes, needs.join(", ")),
+        }
+    }
+}
+
+/// makes a recipe, using up its ingredients. if there is no room for what it makes, nothing is
+/// used up.
+pub fn craft(recipe: &Recipe, inventory: &mut Inventory) -> Result<()> {
+    let missing = recipe.missing(inventory);
+
+    if !missing.is_empty() {
+        bail!(
+            "you need {} more to make the {}.",
+            missing.join(", "),
+            recipe.makes
+        );
+    }
+
+    let Some(item) = items::make(&ItemKey::new(&recipe.makes)) else {
+        bail!("no one knows how to make a {} any more.", recipe.makes);
+    };
+
+    let mut used = Vec::new();
+
+    for (name, n) in &recipe.needs {
+        for _ in 0..*n {
+            if let Some(ingredient) = inventory.find(name).and_then(|key| inventory.take(&key)) {
+                used.push(ingredient);
+            }
+        }
+    }
+
+    let mut made = Vec::new();
+
+    for _ in 0..recipe.count {
+        match inventory.add(item.as_ref()) {
+            Ok(key) => made.push(key),
+            Err(e) => {
+                // put everything back the way it was.
+                for key in made {
+                    let _ = inventory.remove(&key, 1);
+                }
+
+                for ingredient in used {
+                    let _ = inventory.add(ingredient.as_ref());
+                }
+
+                return Err(e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// tries putting some items together. returns the recipe that uses exactly those ingredients, if
+/// there is one. the player doesn't need enough of them to find the recipe, just some of each.
+pub fn experiment(names: &[&str], inventory: &Inventory) -> Result<Option<&'static Recipe>> {
+    if let Some(name) = names.iter().find(|name| have(inventory, name) == 0) {
+        bail!("you don't have any \"{name}\".");
+    }
+
+    // naming an item twice is the same as naming it once, what matters is which items go in.
+    let tried: HashSet<String> = names.iter().map(|name| name.to_lowercase()).collect();
+
+    Ok(RECIPES.iter().find(|recipe| {
+        let needs: HashSet<String> = recipe
+            .needs
+            .iter()
+            .map(|(name, _)| name.to_lowercase())
+            .collect();
+
+        needs == tried
+    }))
+}
+
+/// picks a recipe the player doesn't know yet, for them to learn from some notes.
+pub fn study(known: &[Arc<str>], rng: &mut impl Rng) -> Option<&'static Recipe> {
+    RECIPES
+        .iter()
+        .filter(|recipe| !known.iter().any(|name| **name == *recipe.makes))
+        .collect::<Vec<_>>()
+        .choose(rng)
+        .copied()
+}
@@ -0,0 +1,232 @@
+use crate::player::Stats;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// the odds of landing a hit against someone exactly as fast as the attacker.
+pub const BASE_HIT_CHANCE: f32 = 0.9;
+/// how much each point of speed difference moves the odds of landing a hit.
+pub const HIT_CHANCE_PER_SPEED: f32 = 0.03;
+/// the worst and best odds of landing a hit, no matter the speed difference.
+pub const HIT_CHANCE_RANGE: (f32, f32) = (0.5, 0.99);
+/// the odds of a critical hit before speed is taken into account.
+pub const BASE_CRIT_CHANCE: f32 = 0.05;
+/// how much each point of the attackers speed adds to the odds of a critical hit.
+pub const CRIT_CHANCE_PER_SPEED: f32 = 0.005;
+/// the best odds of a critical hit.
+pub const MAX_CRIT_CHANCE: f32 = 0.25;
+
+/// what kind of damage an attack does. physical damage comes from `str` and is resisted by `def`,
+/// magical damage comes from `mg_str` and is resisted by `mg_def`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DamageType {
+    Physical,
+    Magical,
+}
+
+impl fmt::Display for DamageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DamageType::Physical => write!(f, "physical"),
+            DamageType::Magical => write!(f, "magical"),
+        }
+    }
+}
+
+/// damage that has landed and is about to be applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Damage {
+    /// how much health will be lost.
+    pub amount: u8,
+    pub kind: DamageType,
+    /// true if this was a critical hit.
+    pub crit: bool,
+}
+
+/// how hard the attacker can hit with this kind of damage.
+pub fn power(attacker: &Stats, kind: DamageType) -> u8 {
+    match kind {
+        DamageType::Physical => attacker.str,
+        DamageType::Magical => attacker.mg_str,
+    }
+}
+
+/// how well the defender resists this kind of damage.
+pub fn resistance(defender: &Stats, kind: DamageType) -> u8 {
+    match kind {
+        DamageType::Physical => defender.def,
+        DamageType::Magical => defender.mg_def,
+    }
+}
+
+/// the damage left after the defender's resistance. defense has diminishing returns, it can never
+/// fully block a hit with any power behind it.
+pub fn mitigate(power: u8, resistance: u8) -> u8 {
+    if power == 0 {
+        return 0;
+    }
+
+    let power = power as u16;
+    let dmg = power * power / (power + resistance as u16);
+
+    dmg.clamp(1, u8::MAX as u16) as u8
+}
+
+/// the odds (0.0 to 1.0) of landing a hit. faster attackers hit more often.
+pub fn hit_chance(attacker_speed: u8, defender_speed: u8) -> f32 {
+    let diff = attacker_speed as f32 - defender_speed as f32;
+
+    (BASE_HIT_CHANCE + diff * HIT_CHANCE_PER_SPEED).clamp(HIT_CHANCE_RANGE.0, HIT_CHANCE_RANGE.1)
+}
+
+/// the odds (0.0 to 1.0) of a hit being critical.
+pub fn crit_chance(attacker_speed: u8) -> f32 {
+    (BASE_CRIT_CHANCE + attacker_speed as f32 * CRIT_CHANCE_PER_SPEED).min(MAX_CRIT_CHANCE)
+}
+
+/// a critical hit does half again as much damage.
+pub fn critical(amount: u8) -> u8 {
+    amount.saturating_add(amount / 2)
+}
+
+/// removes the damage from a pool of health without underflowing.
+pub fn apply(hp: u8, dmg: &Damage) -> u8 {
+    hp.saturating_sub(dmg.amount)
+}
+
+/// rolls an attack from one entity against another. returns None if the attack missed.
+pub fn roll(
+    attacker: &Stats,
+    defender: &Stats,
+    kind: DamageType,
+    rng: &mut impl Rng,
+) -> Option<Damage> {
+    if !rng.gen_bool(hit_chance(attacker.speed, defender.speed) as f64) {
+        return None;
+    }
+
+    let amount = mitigate(power(attacker, kind), resistance(defender, kind));
+    let crit = rng.gen_bool(crit_chance(attacker.speed) as f64);
+
+    Some(Damage {
+        amount: if crit { critical(amount) } else { amount },
+        kind,
+        crit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// floats worked out by hand won't match to the last bit.
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    fn stats(str: u8, def: u8, speed: u8) -> Stats {
+        Stats {
+            hp: 20,
+            str,
+            mg_str: 0,
+            def,
+            mg_def: 0,
+            speed,
+        }
+    }
+
+    #[test]
+    fn mitigate_table() {
+        // (power, resistance, damage)
+        let table = [
+            (0, 5, 0),
+            (1, 0, 1),
+            (1, 100, 1),
+            (10, 0, 10),
+            (10, 5, 6),
+            (10, 10, 5),
+            (20, 4, 16),
+            (255, 0, 255),
+            (255, 255, 127),
+        ];
+
+        for (power, resistance, dmg) in table {
+            assert_eq!(
+                mitigate(power, resistance),
+                dmg,
+                "mitigate({power}, {resistance})"
+            );
+        }
+    }
+
+    #[test]
+    fn hit_chance_table() {
+        // (attacker speed, defender speed, chance)
+        let table = [
+            (5, 5, 0.9),
+            (6, 5, 0.93),
+            (5, 6, 0.87),
+            (10, 5, 0.99),
+            (255, 0, 0.99),
+            (0, 255, 0.5),
+        ];
+
+        for (attacker, defender, chance) in table {
+            let got = hit_chance(attacker, defender);
+            assert!(
+                close(got, chance),
+                "hit_chance({attacker}, {defender}) = {got}"
+            );
+        }
+    }
+
+    #[test]
+    fn crit_chance_table() {
+        // (attacker speed, chance)
+        let table = [(0, 0.05), (10, 0.1), (20, 0.15), (40, 0.25), (255, 0.25)];
+
+        for (speed, chance) in table {
+            let got = crit_chance(speed);
+            assert!(close(got, chance), "crit_chance({speed}) = {got}");
+        }
+    }
+
+    #[test]
+    fn critical_table() {
+        // (damage, critical damage)
+        let table = [(0, 0), (1, 1), (2, 3), (10, 15), (200, 255)];
+
+        for (amount, crit) in table {
+            assert_eq!(critical(amount), crit, "critical({amount})");
+        }
+    }
+
+    #[test]
+    fn rolls_are_mitigated_and_seeded() {
+        let attacker = stats(10, 0, 4);
+        let defender = stats(0, 5, 4);
+        let hit = mitigate(10, 5);
+        let roll_all = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..200)
+                .map(|_| roll(&attacker, &defender, DamageType::Physical, &mut rng))
+                .collect::<Vec<_>>()
+        };
+        let rolls = roll_all(7);
+
+        for dmg in rolls.iter().flatten() {
+            match dmg.crit {
+                true => assert_eq!(dmg.amount, critical(hit)),
+                false => assert_eq!(dmg.amount, hit),
+            }
+        }
+
+        // 90% to hit and 7% to crit, 200 rolls should see all three.
+        assert!(rolls.iter().any(|dmg| dmg.is_none()));
+        assert!(rolls.iter().flatten().any(|dmg| dmg.crit));
+        assert!(rolls.iter().flatten().any(|dmg| !dmg.crit));
+        assert_eq!(rolls, roll_all(7));
+    }
+}
@@ -1,18 +1,197 @@
-use crate::battle_logic::{Battlable, BattleAction, BattleState};
-use crate::enemies::{Enemy, Lifeness};
+use crate::battle_logic::{healed, lifeness, Battlable, BattleAction, BattleState, Spell, Target};
+use crate::damage::{self, Damage, DamageType};
+use crate::enemies::mob::Mob;
+use crate::enemies::{Enemy, Lifeness, ENEMY_LEVEL};
+use crate::items::ItemKey;
 use crate::level::Biome;
-use crate::player::Stats;
+use crate::player::{buffed, status_buffs, Stats, StatusEffect};
+use crate::spells::{Bolt, Hex, School};
+use crate::status::{Status, StatusKind};
+use rand::rngs::StdRng;
+use rand::Rng;
 use std::sync::Arc;
 
+/// how many turns pass between a bosses big attacks.
+const CHARGE_EVERY: u16 = 4;
+/// the least and most gold a boss drops.
+const BOSS_GOLD: (u32, u32) = (100, 150);
+
+/// describes how a biome's boss fights. all the bosses share the same AI, this is the data that
+/// makes them different.
+pub struct BossProfile {
+    pub name: &'static str,
+    /// spells the boss casts between physical attacks, as (name, power).
+    pub spells: &'static [(&'static str, u8)],
+    /// the element all of the bosses spells draw on.
+    pub school: School,
+    /// a spell that puts a status effect on the player, as (name, kind, potency). cast in turn
+    /// with the other spells.
+    pub hex: (&'static str, StatusKind, u8),
+    /// the big attack, as (name, power). it always takes a turn of charging first.
+    pub big_attack: (&'static str, u8),
+    /// shown to the player while the boss charges its big attack, so they can defend.
+    pub telegraph: &'static str,
+    /// shown to the player when the boss enrages.
+    pub rage: &'static str,
+    /// the percent of health at which the boss enrages.
+    pub enrage_at: u8,
+    /// the percent of health at which the boss calls its minions.
+    pub summon_at: u8,
+    /// the minions waiting in the lair, as (name, [hp, str, mg_str, def, mg_def, speed]).
+    pub minion: (&'static str, [u8; 6]),
+    pub minion_count: usize,
+    /// the one of a kind item the boss drops.
+    pub unique: &'static str,
+}
+
+const FOREST: BossProfile = BossProfile {
+    name: "the Elder Treant",
+    spells: &[("Thorn Lash", 8), ("Strangling Roots", 6)],
+    school: School::Nature,
+    hex: ("Spore Cloud", StatusKind::Poison, 2),
+    big_attack: ("Falling Timber", 24),
+    telegraph: "the Elder Treant's branches creak as it leans back...",
+    rage: "the Elder Treant's bark splits, sap boiling out of the cracks!",
+    enrage_at: 50,
+    summon_at: 25,
+    minion: ("Sapling", [12, 6, 2, 6, 2, 6]),
+    minion_count: 2,
+    unique: "Heartwood Staff",
+};
+
+const TEMPLE: BossProfile = BossProfile {
+    name: "the Fallen Oracle",
+    spells: &[("Smite", 10), ("Searing Light", 8)],
+    school: School::Light,
+    hex: ("Blinding Light", StatusKind::Blind, 0),
+    big_attack: ("Judgement", 26),
+    telegraph: "the Fallen Oracle raises both hands as light gathers above you...",
+    rage: "the Fallen Oracle's halo cracks and turns black!",
+    enrage_at: 50,
+    summon_at: 25,
+    minion: ("Acolyte", [10, 3, 8, 4, 8, 8]),
+    minion_count: 2,
+    unique: "Oracle's Eye",
+};
+
+const DESERT: BossProfile = BossProfile {
+    name: "the Sand Wyrm",
+    spells: &[("Sandblast", 8), ("Scorching Breath", 10)],
+    school: School::Fire,
+    hex: ("Sunfire", StatusKind::Burn, 3),
+    big_attack: ("Sinkhole", 24),
+    telegraph: "the Sand Wyrm burrows out of sight, the ground starts to shake...",
+    rage: "the Sand Wyrm thrashes, whipping up a sandstorm!",
+    enrage_at: 50,
+    summon_at: 25,
+    minion: ("Scarab", [8, 7, 0, 8, 3, 10]),
+    minion_count: 3,
+    unique: "Wyrmscale Mail",
+};
+
+const MOUNTAINS: BossProfile = BossProfile {
+    name: "the Frost Giant",
+    spells: &[("Ice Shard", 8), ("Blizzard", 6)],
+    school: School::Frost,
+    hex: ("Frostbite", StatusKind::Freeze, 0),
+    big_attack: ("Avalanche", 28),
+    telegraph: "the Frost Giant hefts a boulder over its head...",
+    rage: "the Frost Giant roars, and the mountain answers with a rumble!",
+    enrage_at: 50,
+    summon_at: 25,
+    minion: ("Ice Wolf", [12, 8, 0, 5, 4, 12]),
+    minion_count: 2,
+    unique: "Frostbrand",
+};
+
+const RUINS: BossProfile = BossProfile {
+    name: "the Forgotten King",
+    spells: &[("Soul Drain", 8), ("Grave Chill", 8)],
+    school: School::Shadow,
+    hex: ("Dread Gaze", StatusKind::Stun, 0),
+    big_attack: ("Royal Decree", 24),
+    telegraph: "the Forgotten King points his sceptre at you and begins to chant...",
+    rage: "the Forgotten King rises from his throne!",
+    enrage_at: 50,
+    summon_at: 25,
+    minion: ("Skeleton Guard", [14, 7, 0, 7, 2, 6]),
+    minion_count: 2,
+    unique: "Crown of the Forgotten King",
+};
+
+const DUNGEON: BossProfile = BossProfile {
+    name: "the Warden",
+    spells: &[("Shackle", 6), ("Brand", 10)],
+    school: School::Shadow,
+    hex: ("Shackles", StatusKind::Stun, 0),
+    big_attack: ("Executioner's Swing", 28),
+    telegraph: "the Warden drags his axe back, scraping it along the stones...",
+    rage: "the Warden throws the keys aside. there will be no mercy now!",
+    enrage_at: 50,
+    summon_at: 25,
+    minion: ("Jailer", [14, 8, 0, 8, 3, 7]),
+    minion_count: 2,
+    unique: "Warden's Shackle",
+};
+
+const TOWN: BossProfile = BossProfile {
+    name: "the Guild Master",
+    spells: &[("Knife Throw", 8), ("Poisoned Dart", 6)],
+    school: School::Nature,
+    hex: ("Blinding Powder", StatusKind::Blind, 0),
+    big_attack: ("Assassinate", 26),
+    telegraph: "the Guild Master melts into the shadows...",
+    rage: "the Guild Master whistles, and blades flash from the rooftops!",
+    enrage_at: 50,
+    summon_at: 25,
+    minion: ("Cutthroat", [10, 8, 0, 4, 4, 12]),
+    minion_count: 3,
+    unique: "Guild Master's Signet",
+};
+
+/// returns how the boss of a biome fights.
+pub fn profile(biome: Biome) -> &'static BossProfile {
+    match biome {
+        Biome::Forest => &FOREST,
+        Biome::Temple => &TEMPLE,
+        Biome::Desert => &DESERT,
+        Biome::Mountains => &MOUNTAINS,
+        Biome::Ruins => &RUINS,
+        Biome::Dungeon => &DUNGEON,
+        Biome::Town => &TOWN,
+    }
+}
+
+/// what stage of the fight a boss is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Calm,
+    Enraged,
+}
+
 pub struct Boss {
     name: Arc<str>,
     stats: Stats,
+    /// current health
+    hp: u8,
+    status: Vec<Box<dyn StatusEffect>>,
+    profile: &'static BossProfile,
+    pub phase: Phase,
+    /// true while the big attack is being charged, it lands on the bosses next turn.
+    charging: bool,
+    /// true once the minions have been called.
+    summoned: bool,
+    /// how many turns the boss has taken.
+    turns: u16,
 }
 
 impl Boss {
     pub fn new(biome: Biome) -> Self {
+        let profile = profile(biome);
+
         Self {
-            name: Arc::from(format!("{biome} Boss")),
+            name: Arc::from(profile.name),
+            hp: 100,
             stats: Stats {
                 hp: 100,
                 str: 15,
@@ -21,28 +200,197 @@ impl Boss {
                 mg_def: 15,
                 speed: 10,
             },
+            status: Vec::new(),
+            profile,
+            phase: Phase::Calm,
+            charging: false,
+            summoned: false,
+            turns: 0,
         }
     }
+
+    /// makes the minions that wait in the bosses lair.
+    pub fn minions(&self) -> Vec<Box<dyn Enemy>> {
+        let (name, [hp, str, mg_str, def, mg_def, speed]) = self.profile.minion;
+        let stats = Stats {
+            hp,
+            str,
+            mg_str,
+            def,
+            mg_def,
+            speed,
+        };
+
+        (1..=self.profile.minion_count)
+            .map(|i| Box::new(Mob::new(&format!("{name} #{i}"), stats.clone())) as Box<dyn Enemy>)
+            .collect()
+    }
+
+    /// the bosses health as a percent of its max.
+    fn health(&self) -> u8 {
+        (self.hp as u16 * 100 / self.stats.hp.max(1) as u16) as u8
+    }
 }
 
 impl Enemy for Boss {
+    /// bosses always drop their unique item and a pile of gold. their biomes key goes straight
+    /// onto the players key ring once the boss is dead, see keys::KeyRing.
+    fn drops(&self, rng: &mut StdRng) -> (u32, Vec<ItemKey>) {
+        (
+            rng.gen_range(BOSS_GOLD.0..=BOSS_GOLD.1),
+            vec![ItemKey::new(self.profile.unique)],
+        )
+    }
+
+    /// generates the move that the enemy will take
+    fn get_move(&mut self, state: &mut BattleState) -> BattleAction {
+        let profile = self.profile;
+        self.turns += 1;
+
+        // the big attack always lands the turn after it was telegraphed, giving the player a turn
+        // to defend.
+        if self.charging {
+            self.charging = false;
+            let (name, power) = profile.big_attack;
+
+            return BattleAction::CastSpell((
+                Box::new(Bolt::new(name, profile.school, power)),
+                Target::Enemy(0),
+            ));
+        }
+
+        if self.phase == Phase::Calm && self.health() <= profile.enrage_at {
+            self.phase = Phase::Enraged;
+            self.stats.str = self.stats.str.saturating_mul(3) / 2;
+            self.stats.mg_str = self.stats.mg_str.saturating_mul(3) / 2;
+            self.add_status(Box::new(Status::new(StatusKind::Regen, 3, 2)));
+
+            return BattleAction::Prepare(Arc::from(profile.rage));
+        }
+
+        if !self.summoned && !state.reserves.is_empty() && self.health() <= profile.summon_at {
+            self.summoned = true;
+
+            return BattleAction::Summon;
+        }
+
+        if self.turns % CHARGE_EVERY == 0 {
+            self.charging = true;
+
+            return BattleAction::Prepare(Arc::from(profile.telegraph));
+        }
+
+        // alternate between spells and claws, working through the spells in order with the hex
+        // last.
+        if self.turns % 2 == 0 {
+            return BattleAction::Attack((DamageType::Physical, state.pick_target()));
+        }
+
+        let spell: Box<dyn Spell> = match profile
+            .spells
+            .get(self.turns as usize / 2 % (profile.spells.len() + 1))
+        {
+            Some((name, power)) => Box::new(Bolt::new(name, profile.school, *power)),
+            None => {
+                let (name, kind, potency) = profile.hex;
+                Box::new(Hex::new(name, profile.school, kind, potency))
+            }
+        };
+
+        BattleAction::CastSpell((spell, state.pick_target()))
+    }
+}
+
+impl Battlable for Boss {
     /// returns the enemies name. (eg, "Orc", "Goblin #1", "Gregory the Destroyer of Worlds", etc)
     fn get_name(&self) -> Arc<str> {
         self.name.clone()
     }
 
-    /// generates the move that the enemy will take
-    fn get_move(&mut self, state: &mut BattleState) -> BattleAction {
-        // TODO: write boss AI
-        BattleAction::Attack(self.stats.str)
+    fn get_hp(&self) -> u8 {
+        self.hp
     }
 
-    /// applies damage to the Enemy
-    fn take_damage(&mut self, state: &mut BattleState) -> Lifeness {
-        // TODO: implement damage taking
+    fn get_stats(&self) -> Stats {
+        buffed(&self.stats, status_buffs(&self.status).iter(), ENEMY_LEVEL)
+    }
+
+    fn apply_damage(&mut self, dmg: Damage) -> Lifeness {
+        self.hp = damage::apply(self.hp, &dmg);
 
-        Lifeness::Alive
+        lifeness(self.hp)
+    }
+
+    fn heal(&mut self, amount: u8) -> u8 {
+        let (hp, restored) = healed(self.hp, self.stats.hp, amount);
+        self.hp = hp;
+
+        restored
+    }
+
+    fn status_effects(&mut self) -> &mut Vec<Box<dyn StatusEffect>> {
+        &mut self.status
     }
 }
 
-impl Battlable for Boss {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameState;
+    use crate::player::Player;
+    use crate::world::World;
+
+    /// fights the forest boss with a player who can take a beating but only hits for around an
+    /// eighth of the bosses health, so every phase gets its turn. returns the battle log.
+    fn fight(seed: u64) -> Vec<String> {
+        let boss = Boss::new(Biome::Forest);
+        let minions = boss.minions();
+        let mut battle = BattleState::with_seed(vec![Box::new(boss)], seed);
+        battle.reserves = minions;
+
+        let mut player = Player::new(Some("Tester"));
+        player.stats = Stats {
+            hp: 255,
+            str: 22,
+            mg_str: 0,
+            def: 60,
+            mg_def: 60,
+            speed: 10,
+        };
+        player.hp = 255;
+        let mut world = World::new();
+
+        for _ in 0..40 {
+            if battle.step("attack 1", &mut player, &mut world) {
+                break;
+            }
+
+            if battle.enemies.len() > 1 {
+                break;
+            }
+        }
+
+        battle.log
+    }
+
+    #[test]
+    fn phases_go_calm_then_enrage_then_summon() {
+        let log = fight(42);
+        let position = |line: &str| log.iter().position(|l| l == line);
+        let first_move = log
+            .iter()
+            .position(|l| l.starts_with("the Elder Treant "))
+            .expect("the boss should act");
+        let rage = position(FOREST.rage).expect("the boss should enrage");
+        let summon = position("Sapling #1 joins the battle!").expect("the boss should summon");
+
+        assert!(first_move < rage, "the boss should start out calm");
+        assert!(rage < summon, "the boss should enrage before summoning");
+        assert!(position("Sapling #2 joins the battle!").is_some());
+    }
+
+    #[test]
+    fn boss_fights_are_replayable() {
+        assert_eq!(fight(42), fight(42));
+    }
+}
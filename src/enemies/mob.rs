@@ -0,0 +1,191 @@
+use crate::battle_logic::{healed, lifeness, Battlable, BattleAction, BattleState};
+use crate::damage::{self, Damage, DamageType};
+use crate::enemies::{Enemy, Lifeness, ENEMY_LEVEL};
+use crate::level::Biome;
+use crate::player::{buffed, status_buffs, Stats, StatusEffect};
+use std::fmt;
+use std::sync::Arc;
+
+/// a regular enemy. mobs are plain data so they can wait in a zone's MobSpawner until a battle
+/// starts. status effects are not part of that data, they wear off when a mob is cloned and are
+/// ignored when comparing mobs.
+pub struct Mob {
+    name: Arc<str>,
+    stats: Stats,
+    /// current health
+    hp: u8,
+    status: Vec<Box<dyn StatusEffect>>,
+}
+
+impl Mob {
+    pub fn new(name: &str, stats: Stats) -> Self {
+        Self {
+            name: Arc::from(name),
+            hp: stats.hp,
+            stats,
+            status: Vec::new(),
+        }
+    }
+
+    /// sets the mobs current health, up to its max.
+    pub fn set_hp(&mut self, hp: u8) {
+        self.hp = hp.min(self.stats.hp);
+    }
+}
+
+impl Clone for Mob {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            stats: self.stats.clone(),
+            hp: self.hp,
+            status: Vec::new(),
+        }
+    }
+}
+
+impl PartialEq for Mob {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.stats == other.stats && self.hp == other.hp
+    }
+}
+
+impl Eq for Mob {}
+
+impl fmt::Debug for Mob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mob")
+            .field("name", &self.name)
+            .field("stats", &self.stats)
+            .field("hp", &self.hp)
+            .finish()
+    }
+}
+
+impl Enemy for Mob {
+    /// generates the move that the enemy will take
+    fn get_move(&mut self, state: &mut BattleState) -> BattleAction {
+        // mobs attack with whichever kind of strength they have more of.
+        let kind = if self.stats.mg_str > self.stats.str {
+            DamageType::Magical
+        } else {
+            DamageType::Physical
+        };
+
+        BattleAction::Attack((kind, state.pick_target()))
+    }
+}
+
+impl Battlable for Mob {
+    /// returns the enemies name. (eg, "Orc", "Goblin #1", "Gregory the Destroyer of Worlds", etc)
+    fn get_name(&self) -> Arc<str> {
+        self.name.clone()
+    }
+
+    fn get_hp(&self) -> u8 {
+        self.hp
+    }
+
+    fn get_stats(&self) -> Stats {
+        buffed(&self.stats, status_buffs(&self.status).iter(), ENEMY_LEVEL)
+    }
+
+    fn apply_damage(&mut self, dmg: Damage) -> Lifeness {
+        self.hp = damage::apply(self.hp, &dmg);
+
+        lifeness(self.hp)
+    }
+
+    fn heal(&mut self, amount: u8) -> u8 {
+        let (hp, restored) = healed(self.hp, self.stats.hp, amount);
+        self.hp = hp;
+
+        restored
+    }
+
+    fn status_effects(&mut self) -> &mut Vec<Box<dyn StatusEffect>> {
+        &mut self.status
+    }
+}
+
+/// one kind of enemy a MobSpawner can spawn.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SpawnEntry {
+    pub name: &'static str,
+    pub stats: Stats,
+    /// how likely this entry is to be picked relative to the rest of the table.
+    pub weight: u8,
+    /// the smallest and largest group this enemy spawns in.
+    pub group: (u8, u8),
+}
+
+const fn entry(
+    name: &'static str,
+    [hp, str, mg_str, def, mg_def, speed]: [u8; 6],
+    weight: u8,
+    group: (u8, u8),
+) -> SpawnEntry {
+    SpawnEntry {
+        name,
+        stats: Stats {
+            hp,
+            str,
+            mg_str,
+            def,
+            mg_def,
+            speed,
+        },
+        weight,
+        group,
+    }
+}
+
+// entry(name, [hp, str, mg_str, def, mg_def, speed], weight, group)
+const FOREST: [SpawnEntry; 3] = [
+    entry("Wolf", [12, 4, 0, 2, 1, 6], 5, (1, 3)),
+    entry("Goblin", [14, 5, 0, 3, 1, 4], 4, (1, 2)),
+    entry("Dryad", [10, 1, 5, 2, 4, 5], 1, (1, 1)),
+];
+const TEMPLE: [SpawnEntry; 3] = [
+    entry("Acolyte", [12, 2, 5, 2, 4, 4], 5, (1, 3)),
+    entry("Stone Guardian", [24, 6, 0, 7, 2, 2], 2, (1, 1)),
+    entry("Wraith", [10, 1, 6, 1, 6, 6], 2, (1, 2)),
+];
+const DESERT: [SpawnEntry; 3] = [
+    entry("Scorpion", [10, 5, 0, 4, 1, 5], 5, (1, 3)),
+    entry("Bandit", [16, 5, 0, 3, 2, 5], 3, (2, 3)),
+    entry("Sand Wraith", [12, 1, 6, 1, 5, 6], 1, (1, 1)),
+];
+const MOUNTAINS: [SpawnEntry; 3] = [
+    entry("Harpy", [12, 4, 1, 2, 2, 7], 5, (1, 3)),
+    entry("Ice Imp", [10, 1, 6, 2, 4, 5], 3, (1, 2)),
+    entry("Rock Troll", [28, 7, 0, 6, 1, 2], 1, (1, 1)),
+];
+const RUINS: [SpawnEntry; 3] = [
+    entry("Skeleton", [12, 5, 0, 3, 1, 4], 5, (1, 3)),
+    entry("Ghoul", [16, 6, 0, 2, 2, 4], 3, (1, 2)),
+    entry("Cultist", [12, 1, 6, 2, 4, 4], 2, (1, 2)),
+];
+const DUNGEON: [SpawnEntry; 3] = [
+    entry("Rat Swarm", [8, 3, 0, 1, 1, 7], 5, (2, 3)),
+    entry("Slime", [18, 3, 2, 5, 1, 2], 3, (1, 2)),
+    entry("Jailer", [22, 6, 0, 5, 2, 3], 1, (1, 1)),
+];
+const TOWN: [SpawnEntry; 3] = [
+    entry("Stray Dog", [10, 3, 0, 1, 1, 6], 5, (1, 2)),
+    entry("Pickpocket", [10, 3, 0, 2, 2, 7], 3, (1, 1)),
+    entry("Thug", [18, 6, 0, 3, 1, 3], 2, (1, 2)),
+];
+
+/// the enemies that roam a biome.
+pub fn spawn_table(biome: Biome) -> &'static [SpawnEntry] {
+    match biome {
+        Biome::Forest => &FOREST,
+        Biome::Temple => &TEMPLE,
+        Biome::Desert => &DESERT,
+        Biome::Mountains => &MOUNTAINS,
+        Biome::Ruins => &RUINS,
+        Biome::Dungeon => &DUNGEON,
+        Biome::Town => &TOWN,
+    }
+}
@@ -1,8 +1,21 @@
 use crate::battle_logic::{Battlable, BattleAction, BattleState};
-use std::sync::Arc;
+use crate::items::ItemKey;
+use crate::level::Biome;
+use crate::loot;
+use crate::zone::ZoneType;
+use mob::{spawn_table, Mob, SpawnEntry};
+use rand::prelude::*;
+use rand::rngs::StdRng;
 // use serde::{Deserialize, Serialize};
 
 pub mod boss;
+pub mod mob;
+
+/// how long (in world clock ticks) a cleared zone stays empty before enemies move back in.
+pub const RESPAWN_TIME: u32 = 60;
+
+/// enemies don't level up, so buffs that scale with level treat them as this level.
+pub const ENEMY_LEVEL: u8 = 1;
 
 pub enum Lifeness {
     Alive,
@@ -16,14 +29,14 @@ pub trait Enemy: Battlable {
     // where
     //     Self: Sized;
 
-    /// returns the enemies name. (eg, "Orc", "Goblin #1", "Gregory the Destroyer of Worlds", etc)
-    fn get_name(&self) -> Arc<str>;
-
     /// generates the move that the enemy will take
     fn get_move(&mut self, state: &mut BattleState) -> BattleAction;
 
-    /// applies damage to the Enemy
-    fn take_damage(&mut self, state: &mut BattleState) -> Lifeness;
+    /// rolls what the enemy leaves behind when it's defeated, as (gold, items). see
+    /// loot::drop_table.
+    fn drops(&self, rng: &mut StdRng) -> (u32, Vec<ItemKey>) {
+        loot::roll_drops(&self.get_name(), &self.get_stats(), rng)
+    }
 }
 
 // #[derive(Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -34,7 +47,104 @@ pub trait Enemy: Battlable {
 //     maker_adr: I2cAdr,
 // }
 
+/// the chance (out of 100) of an encounter each time the player enters or rests in a zone.
+fn encounter_chance(kind: &ZoneType) -> u8 {
+    match kind {
+        ZoneType::Shelter => 5,
+        ZoneType::OpenPath => 25,
+        ZoneType::Coredor => 30,
+        ZoneType::Temple => 20,
+        ZoneType::Mountains => 30,
+        ZoneType::TreasureRoom => 40,
+        ZoneType::Sanctuary | ZoneType::BossEntry | ZoneType::Wall => 0,
+    }
+}
+
+/// spawns the enemies of a single zone.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct MobSpawner {}
+pub struct MobSpawner {
+    /// the enemies that can spawn in this zone.
+    pub table: &'static [SpawnEntry],
+    /// the chance (out of 100) of an encounter each time the player enters or rests in the zone.
+    pub chance: u8,
+    /// seeds every roll so the same zone at the same time always spawns the same enemies.
+    pub seed: u64,
+    /// enemies that have spawned but not been beaten yet. they wait here for the player.
+    pub lurking: Vec<Mob>,
+    /// the world clock when the zone was last cleared.
+    pub cleared_at: Option<u32>,
+}
+
+impl MobSpawner {
+    /// makes a spawner for a zone, or None if nothing ever spawns in that kind of zone.
+    pub fn new(biome: Biome, kind: &ZoneType, seed: u64) -> Option<Self> {
+        let chance = encounter_chance(kind);
+
+        (chance > 0).then(|| Self {
+            table: spawn_table(biome),
+            chance,
+            seed,
+            lurking: Vec::new(),
+            cleared_at: None,
+        })
+    }
 
-impl MobSpawner {}
+    /// rolls for an encounter at the given world time. enemies already lurking in the zone are
+    /// always encountered. returns the group to fight, if any.
+    pub fn roll(&mut self, clock: u32) -> Option<Vec<Box<dyn Enemy>>> {
+        if self.lurking.is_empty() {
+            if self
+                .cleared_at
+                .is_some_and(|cleared| clock.saturating_sub(cleared) < RESPAWN_TIME)
+            {
+                return None;
+            }
+
+            let mut rng = StdRng::seed_from_u64(self.seed ^ (clock as u64).rotate_left(17));
+
+            if rng.gen_range(0..100) >= self.chance {
+                return None;
+            }
+
+            let kind = self.table.choose_weighted(&mut rng, |e| e.weight).ok()?;
+            let n = rng.gen_range(kind.group.0..=kind.group.1);
+
+            self.lurking = (1..=n)
+                .map(|i| match n {
+                    1 => Mob::new(kind.name, kind.stats.clone()),
+                    _ => Mob::new(&format!("{} #{i}", kind.name), kind.stats.clone()),
+                })
+                .collect();
+        }
+
+        Some(
+            self.lurking
+                .iter()
+                .map(|mob| Box::new(mob.clone()) as Box<dyn Enemy>)
+                .collect(),
+        )
+    }
+
+    /// updates the lurking enemies after a battle the player didn't win. the ones that were
+    /// beaten are gone, the rest keep their wounds.
+    pub fn survivors(&mut self, enemies: &[Box<dyn Enemy>]) {
+        self.lurking.retain_mut(|mob| {
+            match enemies
+                .iter()
+                .find(|enemy| enemy.get_name() == mob.get_name())
+            {
+                Some(enemy) => {
+                    mob.set_hp(enemy.get_hp());
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+
+    /// marks the zone as cleared. it stays empty for RESPAWN_TIME ticks.
+    pub fn clear(&mut self, clock: u32) {
+        self.lurking.clear();
+        self.cleared_at = Some(clock);
+    }
+}
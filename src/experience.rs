@@ -0,0 +1,66 @@
+use crate::player::{Buff, BuffType, Stats};
+
+/// the highest level the player can reach.
+pub const MAX_LEVEL: u8 = 50;
+/// the xp needed to get from level 1 to level 2.
+const XP_BASE: f32 = 40.0;
+/// how much more xp each level needs than the one before it.
+const XP_CURVE: f32 = 1.25;
+/// how much the players max mana goes up every level.
+pub const MANA_PER_LEVEL: u8 = 2;
+
+/// spells the player works out for themselves on reaching a level. (level, spell)
+pub const UNLOCKS: [(u8, &str); 4] = [
+    (2, "Heal"),
+    (4, "Shield"),
+    (6, "Fireball"),
+    (9, "Call Wolf"),
+];
+
+/// the xp needed to get from `level` to the next one.
+pub fn to_next(level: u8) -> u32 {
+    (XP_BASE * XP_CURVE.powi(level.saturating_sub(1) as i32)).round() as u32
+}
+
+/// the xp for defeating an enemy. tougher enemies are worth more.
+pub fn reward(stats: &Stats) -> u32 {
+    stats.power() as u32
+}
+
+/// how the players stats grow as they level, evaluated at the number of levels gained. health
+/// grows slowly at first and then quickly (Exp), everything else grows quickly at first and then
+/// tapers off (Log). training a stat raises its factor, see train.
+pub fn growth() -> Buff {
+    Buff {
+        hp: BuffType::Exp(8),
+        str: BuffType::Log(2),
+        mg_str: BuffType::Log(2),
+        def: BuffType::Log(2),
+        mg_def: BuffType::Log(1),
+        speed: BuffType::Log(1),
+    }
+}
+
+/// the growth factor for a stat by its name (as shown on the stat sheet), if there is such a
+/// stat.
+pub fn factor<'a>(growth: &'a mut Buff, stat: &str) -> Option<&'a mut BuffType> {
+    match stat.to_lowercase().as_str() {
+        "hp" => Some(&mut growth.hp),
+        "str" => Some(&mut growth.str),
+        "mg_str" => Some(&mut growth.mg_str),
+        "def" => Some(&mut growth.def),
+        "mg_def" => Some(&mut growth.mg_def),
+        "speed" => Some(&mut growth.speed),
+        _ => None,
+    }
+}
+
+/// puts a training point into a growth factor. since growth scales with level, a point is worth
+/// more the higher the player gets.
+pub fn train(factor: &mut BuffType) {
+    match factor {
+        BuffType::Log(n) | BuffType::Exp(n) => *n = n.saturating_add(1),
+        BuffType::Const(n) => *n = n.saturating_add(1),
+        BuffType::Multiplier(n) => *n += 0.1,
+    }
+}
@@ -0,0 +1,180 @@
+use crate::level::Biome;
+use crate::{I2cCom, Player};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// the most victories a hall of fame remembers. the lowest scores are forgotten first.
+pub const MAX_MEMORIALS: usize = 20;
+
+/// a player who beat the game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Memorial {
+    pub name: Arc<str>,
+    pub score: u16,
+    /// how long the game took, in seconds of play.
+    pub time: u32,
+    /// the biomes cleared and the seed of the world each was cleared in.
+    pub worlds: Vec<(Biome, u64)>,
+}
+
+impl Memorial {
+    /// remembers the players victory. the worlds come from the keys on their key ring.
+    pub fn new(player: &Player) -> Self {
+        Self {
+            name: player.name.clone(),
+            score: player.card.total(),
+            time: player.started.elapsed().as_secs().min(u32::MAX as u64) as u32,
+            worlds: Biome::ALL
+                .into_iter()
+                .filter_map(|biome| player.keys.get(biome))
+                .map(|key| (key.biome, key.seed))
+                .collect(),
+        }
+    }
+}
+
+/// how long a game took, as the player sees it.
+fn played(secs: u32) -> String {
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, m, s) => format!("{m}m {s:02}s"),
+        (h, m, _) => format!("{h}h {m:02}m"),
+    }
+}
+
+/// everyone this device knows of who beat the game, best score first. halls are swapped between
+/// nodes so each board remembers everyone it has met.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HallOfFame {
+    entries: Vec<Memorial>,
+    /// true if there is something new that hasn't been saved yet.
+    #[serde(skip)]
+    changed: bool,
+}
+
+impl HallOfFame {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entries(&self) -> &[Memorial] {
+        &self.entries
+    }
+
+    /// adds a victory, unless it's already in the hall or it scored too low to make it into a
+    /// full one. returns true if it was added.
+    pub fn record(&mut self, memorial: Memorial) -> bool {
+        if self.entries.contains(&memorial) {
+            return false;
+        }
+
+        self.entries.push(memorial.clone());
+        self.entries
+            .sort_by(|a, b| b.score.cmp(&a.score).then(a.time.cmp(&b.time)));
+        self.entries.truncate(MAX_MEMORIALS);
+
+        let added = self.entries.contains(&memorial);
+        self.changed |= added;
+
+        added
+    }
+
+    /// adds everyone from another hall of fame that isn't in this one already.
+    pub fn merge(&mut self, other: HallOfFame) {
+        for memorial in other.entries {
+            self.record(memorial);
+        }
+    }
+
+    /// true if the hall has changed since this was last called, so it can be saved.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// the hall of fame as the player sees it.
+    pub fn listing(&self) -> Vec<String> {
+        let mut lines = vec!["hall of fame:".to_string()];
+
+        if self.entries.is_empty() {
+            lines.push("  no one has beaten the game. yet.".to_string());
+        }
+
+        for (i, memorial) in self.entries.iter().enumerate() {
+            let worlds: Vec<String> = memorial
+                .worlds
+                .iter()
+                .map(|(biome, seed)| format!("{biome} ({seed:x})"))
+                .collect();
+
+            lines.push(format!(
+                "{:>2}) {} - {} points in {}",
+                i + 1,
+                memorial.name,
+                memorial.score,
+                played(memorial.time)
+            ));
+            lines.push(format!("    {}", worlds.join(", ")));
+        }
+
+        lines
+    }
+}
+
+/// halls of fame are sent as a line of json, for saving and for swapping with other nodes.
+impl I2cCom<HallOfFame> for HallOfFame {
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut data = serde_json::to_vec(self)?;
+        data.push(b'\n');
+
+        Ok(data)
+    }
+
+    fn decode_from(&self, data: &[u8]) -> Result<HallOfFame> {
+        // packets are padded with null bytes, only the first line is the hall.
+        let line = data.split(|byte| *byte == b'\n').next().unwrap_or(data);
+
+        Ok(serde_json::from_slice(line)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memorial(name: &str, score: u16) -> Memorial {
+        Memorial {
+            name: Arc::from(name),
+            score,
+            time: 600,
+            worlds: vec![(Biome::Forest, 7)],
+        }
+    }
+
+    #[test]
+    fn low_scores_stay_out_of_a_full_hall() {
+        let mut fame = HallOfFame::new();
+
+        for i in 0..MAX_MEMORIALS {
+            assert!(fame.record(memorial(&format!("hero {i}"), 100 + i as u16)));
+        }
+
+        assert!(fame.take_changed());
+        assert!(!fame.record(memorial("hero 0", 100)), "already in the hall");
+        assert!(!fame.record(memorial("latecomer", 50)), "scored too low");
+        assert!(!fame.take_changed());
+        assert_eq!(fame.entries().len(), MAX_MEMORIALS);
+
+        // a hall swapped in from another node with the same low score changes nothing either.
+        let mut theirs = HallOfFame::new();
+        theirs.record(memorial("latecomer", 50));
+        fame.merge(theirs);
+        assert!(!fame.take_changed());
+
+        // a better score bumps the lowest one out.
+        assert!(fame.record(memorial("champion", 500)));
+        assert!(fame.take_changed());
+        assert_eq!(fame.entries()[0].name.as_ref(), "champion");
+        assert_eq!(fame.entries().len(), MAX_MEMORIALS);
+        assert!(!fame.entries().contains(&memorial("hero 0", 100)));
+    }
+}
@@ -1,27 +1,722 @@
+use crate::allies::{Companion, MAX_PARTY};
+use crate::battle_logic::{Battlable, BattleOutcome, BattleState, Effect};
+use crate::crafting;
+use crate::items::{self, Category, ItemKey, Use};
+use crate::keys::{BiomeKey, GateState};
+use crate::level::{zone_seed, Biome, Coords, Level, EAST, NORTH, SOUTH, WEST};
+use crate::loot::{self, Stash};
+use crate::lore;
+use crate::shop::ShopState;
+use crate::spells::{self, TargetMode};
+use crate::status;
 use crate::world::World;
+use crate::zone::ZoneType;
 use crate::Player;
 use anyhow::bail;
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::Arc;
 
 /// trait for a game state Explore, Battle, Interact, etc.
 pub trait GameState {
     /// a generic step function to step through the state manchine, returns true when ready to exit
     /// (battle ended, etc)
-    fn step(&mut self, cmd: &str, player: &Player) -> bool;
+    fn step(&mut self, cmd: &str, player: &mut Player, world: &mut World) -> bool;
 
     /// used to statically check if the state is complete
     fn is_done(&self) -> bool;
+
+    /// returns a state that should be pushed on top of this one (a battle, a shop, etc). called
+    /// after every step.
+    fn next_state(&mut self) -> Option<Rc<RefCell<dyn GameState>>> {
+        None
+    }
+
+    /// called when the state above this one is popped and this state is active again.
+    #[allow(unused_variables)]
+    fn resume(&mut self, player: &mut Player, world: &mut World) {}
+}
+
+/// how much time passes when the player rests.
+const REST_TIME: u32 = 10;
+/// how much time passes when the player searches a zone.
+const SEARCH_TIME: u32 = 3;
+
+/// parses a compass direction from a command argument.
+fn direction(name: &str) -> Option<(isize, isize)> {
+    match name {
+        "north" | "n" => Some(NORTH),
+        "south" | "s" => Some(SOUTH),
+        "east" | "e" => Some(EAST),
+        "west" | "w" => Some(WEST),
+        _ => None,
+    }
+}
+
+/// true if the argument to `read` means the sanctuary sign rather than an item. (`read` on its
+/// own reads the sign too)
+fn names_sign(what: &str) -> bool {
+    what.is_empty() || what.eq_ignore_ascii_case("sign")
+}
+
+/// the zones in a level the player can fast travel to. (visited Sanctuaries and Shelters)
+fn waypoints(level: &Level) -> Vec<Coords> {
+    let mut stops = Vec::new();
+
+    for y in 0..level.dim.1 as usize {
+        for x in 0..level.dim.0 as usize {
+            let zone = &level[(x, y)];
+
+            if zone.visited && matches!(zone.feild_type, ZoneType::Sanctuary | ZoneType::Shelter) {
+                stops.push((x, y));
+            }
+        }
+    }
+
+    stops
 }
 
 pub struct ExploreState {
+    /// which level in the world the player is in.
+    pub level: usize,
     /// which zone in the level the player is in.
     pub loc: (u8, u8),
+    /// the zone the player was in before this one. fleeing from a battle leads back here.
+    prev: Option<(u8, u8)>,
+    /// true while the player is being asked if they really want to enter the boss portal.
+    confirm_portal: bool,
+    /// the boss battle and the bosses name, kept so the boss and its minions can be sent back to
+    /// the lair if the player escapes.
+    lair: Option<(Arc<str>, Rc<RefCell<BattleState>>)>,
+    /// a battle with the enemies of a zone, kept so the zone can be cleared once they are beaten.
+    encounter: Option<(Coords, Rc<RefCell<BattleState>>)>,
+    /// a state to push onto the stack once this step is over.
+    next: Option<Rc<RefCell<dyn GameState>>>,
 }
 
 impl ExploreState {
-    fn new() -> Self {
-        Self { loc: (128, 128) }
+    fn new(player: &mut Player, world: &mut World) -> Self {
+        let level = &mut world.levels[0];
+        let warp_in = (level.warp_in.0 as usize, level.warp_in.1 as usize);
+        level[warp_in].visited = true;
+        player.card.discover(level.explorable());
+
+        Self {
+            level: 0,
+            loc: (warp_in.0 as u8, warp_in.1 as u8),
+            prev: None,
+            confirm_portal: false,
+            lair: None,
+            encounter: None,
+            next: None,
+        }
+    }
+
+    pub fn coords(&self) -> Coords {
+        (self.loc.0 as usize, self.loc.1 as usize)
+    }
+
+    /// moves the player into the zone at `loc`, one step of in game time.
+    fn enter(&mut self, loc: Coords, player: &mut Player, world: &mut World) {
+        self.pass_time(1, player, world);
+        let level = &mut world.levels[self.level];
+
+        if !level[loc].visited {
+            player.card.discover(level.explorable());
+        }
+
+        level[loc].visited = true;
+        self.prev = Some(self.loc);
+        self.loc = (loc.0 as u8, loc.1 as u8);
+    }
+
+    /// rolls for enemies in the current zone. returns true if the player was ambushed, in which
+    /// case a battle will be pushed after this step.
+    fn ambush(&mut self, world: &mut World) -> bool {
+        let zone = &mut world.levels[self.level][self.coords()];
+
+        let Some(enemies) = zone
+            .enemies
+            .as_mut()
+            .and_then(|spawner| spawner.roll(world.clock))
+        else {
+            return false;
+        };
+
+        let names: Vec<_> = enemies.iter().map(|enemy| enemy.get_name()).collect();
+        println!(
+            "you are ambushed in the {} by {}!",
+            zone.title(),
+            names.join(", ")
+        );
+
+        let battle = Rc::new(RefCell::new(BattleState::new(enemies)));
+        self.encounter = Some((self.coords(), battle.clone()));
+        self.next = Some(battle);
+
+        true
+    }
+
+    /// moves the world clock forward. status effects on the player tick every
+    /// status::EXPLORE_TICK_TIME while exploring, but they can't finish the player off.
+    fn pass_time(&self, time: u32, player: &mut Player, world: &mut World) {
+        for _ in 0..time {
+            world.clock += 1;
+
+            if world.clock % status::EXPLORE_TICK_TIME != 0 {
+                continue;
+            }
+
+            for (effect, tick, worn_off) in status::tick_all(&mut player.status) {
+                if tick.damage > 0 {
+                    let lost = tick.damage.min(player.hp.saturating_sub(1));
+                    player.hp -= lost;
+                    println!("you suffer from {effect}, losing {lost} hp.");
+                }
+
+                if tick.heal > 0 {
+                    let restored = player.heal(tick.heal);
+                    println!("your {effect} restores {restored} hp.");
+                }
+
+                if worn_off {
+                    println!("your {effect} wears off.");
+                }
+            }
+        }
+    }
+
+    /// passes REST_TIME in the current zone, restoring the players mana. resting gives nearby
+    /// enemies time to find the player.
+    fn rest(&mut self, player: &mut Player, world: &mut World) {
+        self.pass_time(REST_TIME, player, world);
+        player.mana.refill();
+        println!("you rest for a while.");
+        self.ambush(world);
+    }
+
+    /// brings a defeated player back to life in the levels warp in sanctuary.
+    fn revive(&mut self, player: &mut Player, world: &mut World) {
+        let warp_in = world.levels[self.level].warp_in;
+
+        player.card.died();
+        player.heal(u8::MAX);
+        player.mana.refill();
+        player.status.clear();
+
+        for ally in player.party.iter_mut() {
+            ally.heal(u8::MAX);
+            ally.status_effects().clear();
+        }
+        self.loc = (warp_in.0 as u8, warp_in.1 as u8);
+        self.prev = None;
+
+        println!("everything goes dark...");
+        println!("you wake in the sanctuary, your wounds mended.");
+        self.look(world);
+    }
+
+    /// tells the player where they are.
+    fn look(&self, world: &World) {
+        let zone = &world.levels[self.level][self.coords()];
+
+        println!("{}", zone.title());
+
+        if !zone.welcome.is_empty() {
+            println!("{}", zone.welcome);
+        }
+
+        if zone.feild_type == ZoneType::Sanctuary {
+            println!("a gate with seven keyholes stands at the back. (keys, unlock)");
+        }
+
+        if let Some(hireling) = zone.companion {
+            println!("a {} is looking for work.", hireling.name);
+        }
+
+        if let Some(shop) = &zone.shop {
+            println!("{} is open for trade. (shop)", shop.title());
+        }
+
+        if let Some(loot) = &zone.loot {
+            if !loot.ground.is_empty() {
+                let items: Vec<String> = loot.ground.iter().map(|key| key.to_string()).collect();
+                println!("lying here: {}.", items.join(", "));
+            }
+
+            match &loot.chest {
+                Some(chest) if chest.locked => println!("there is a locked chest here."),
+                Some(_) => println!("there is a chest here."),
+                None => {}
+            }
+        }
+    }
+
+    /// tells the player where they are in more detail.
+    fn describe(&self, world: &World) {
+        let zone = &world.levels[self.level][self.coords()];
+
+        self.look(world);
+
+        if !zone.desc.is_empty() {
+            println!("{}", zone.desc);
+        }
+    }
+
+    /// reads the sign in a Sanctuary.
+    fn read_sign(&self, world: &World) -> Vec<String> {
+        let level = &world.levels[self.level];
+
+        if level[self.coords()].feild_type != ZoneType::Sanctuary {
+            return vec!["there is no sign here.".into()];
+        }
+
+        lore::sign(level, self.coords())
+    }
+
+    /// studies the murals of a Temple. each temple teaches one spell from the spell book.
+    fn study(&self, player: &mut Player, world: &World) {
+        let level = &world.levels[self.level];
+
+        if level[self.coords()].feild_type != ZoneType::Temple {
+            println!("there is nothing to study here.");
+            return;
+        }
+
+        let seed = zone_seed(level.seed, self.coords());
+        let spell = spells::BOOK[seed as usize % spells::BOOK.len()];
+
+        if player.learn(spell) {
+            println!("you study the murals on the walls and learn to cast {spell}!");
+        } else {
+            println!("the murals here teach {spell}, which you already know.");
+        }
+    }
+
+    /// uses an item on the player. only potions and the like work outside of battle.
+    fn use_item(&self, name: &str, player: &mut Player) {
+        let Some(key) = player.inventory.find(name) else {
+            println!("you don't have a \"{name}\". try inventory");
+            return;
+        };
+        let Some(mut item) = items::make(&key) else {
+            return;
+        };
+
+        if matches!(items::lookup(&key.name), Some(def) if def.does == Use::Recipe) {
+            self.read_notes(&key, player);
+            return;
+        }
+
+        let stats = player.get_stats();
+        let usable = item.category() == Category::Consumable
+            && matches!(item.target_mode(), TargetMode::TheSelf | TargetMode::Ally);
+
+        match usable.then(|| item.use_on(&stats, &stats)).flatten() {
+            Some(Effect::Heal(amount)) => {
+                let restored = player.heal(amount);
+                println!("you use the {} and recover {restored} hp.", item.name());
+            }
+            Some(Effect::Status(effect)) => {
+                println!(
+                    "you use the {}. you are now affected by {}.",
+                    item.name(),
+                    effect.display_name()
+                );
+                player.add_status(effect);
+            }
+            _ => {
+                println!("you can't use the {} here.", item.name());
+                return;
+            }
+        }
+
+        let _ = player.inventory.remove(&key, 1);
+    }
+
+    /// reads some crafting notes, learning a recipe the player doesn't know yet.
+    fn read_notes(&self, key: &ItemKey, player: &mut Player) {
+        let Some(recipe) = crafting::study(&player.recipes, &mut rand::thread_rng()) else {
+            println!("the {} teach you nothing you don't already know.", key.name);
+            return;
+        };
+
+        player.learn_recipe(&recipe.makes);
+        let _ = player.inventory.remove(key, 1);
+        println!(
+            "you pore over the {} and learn how to make the {}!",
+            key.name, recipe.makes
+        );
+    }
+
+    /// lists the recipes the player knows and what they need for each.
+    fn recipes(&self, player: &Player) {
+        println!("you know how to make:");
+
+        for recipe in crafting::recipes() {
+            if player.knows_recipe(&recipe.makes) {
+                println!("  {}", recipe.describe(&player.inventory));
+            }
+        }
+    }
+
+    /// crafts an item. `what` is either a recipe the player knows, or some items joined by "+"
+    /// to experiment with. crafting can only be done in a Shelter or Sanctuary.
+    fn craft(&self, what: &str, player: &mut Player, world: &World) {
+        if !crafting::workshop(&world.levels[self.level][self.coords()].feild_type) {
+            println!("you need somewhere safe to work. find a Shelter or Sanctuary.");
+            return;
+        }
+
+        if what.is_empty() {
+            println!("craft what? (see recipes, or try craft <item> + <item>)");
+            return;
+        }
+
+        let recipe = if what.contains('+') {
+            let names: Vec<&str> = what.split('+').map(str::trim).collect();
+
+            match crafting::experiment(&names, &player.inventory) {
+                Ok(Some(recipe)) => recipe,
+                Ok(None) => {
+                    println!("you fiddle with them for a while, but nothing useful comes of it.");
+                    return;
+                }
+                Err(e) => {
+                    println!("{e}");
+                    return;
+                }
+            }
+        } else {
+            match crafting::recipe(what).filter(|recipe| player.knows_recipe(&recipe.makes)) {
+                Some(recipe) => recipe,
+                None => {
+                    println!("you don't know how to make a \"{what}\". (see recipes)");
+                    return;
+                }
+            }
+        };
+
+        if player.learn_recipe(&recipe.makes) {
+            println!("you've worked out how to make the {}!", recipe.makes);
+        }
+
+        match crafting::craft(recipe, &mut player.inventory) {
+            Ok(()) if recipe.count == 1 => println!("you make the {}.", recipe.makes),
+            Ok(()) => println!("you make {} {}.", recipe.count, recipe.makes),
+            Err(e) => println!("{e}"),
+        }
+    }
+
+    /// searches the zone for hidden loot. searching takes a while, so something may find the
+    /// player first.
+    fn search(&mut self, player: &mut Player, world: &mut World) {
+        let zone = &mut world.levels[self.level][self.coords()];
+
+        let found = match &mut zone.loot {
+            Some(loot) if !loot.searched => loot.search(),
+            _ => Vec::new(),
+        };
+
+        if found.is_empty() {
+            println!("you search the area but find nothing.");
+        }
+
+        for key in found {
+            println!("you find a {key}!");
+        }
+
+        self.pass_time(SEARCH_TIME, player, world);
+        self.ambush(world);
+    }
+
+    /// picks up an item lying in the zone. "all" picks up everything there is room for.
+    fn take(&self, name: &str, player: &mut Player, world: &mut World) {
+        let Some(loot) = &mut world.levels[self.level][self.coords()].loot else {
+            println!("there is nothing here to take.");
+            return;
+        };
+
+        let names: Vec<String> = match name {
+            "all" => loot.ground.iter().map(|key| key.name.to_string()).collect(),
+            name => vec![name.to_string()],
+        };
+
+        for name in names {
+            let Some(key) = loot.take(&name) else {
+                println!("there is no \"{name}\" here.");
+                continue;
+            };
+            let Some(item) = items::make(&key) else {
+                continue;
+            };
+
+            match player.inventory.add(item.as_ref()) {
+                Ok(_) => {
+                    player.card.find(&key.name);
+                    println!("you pick up the {}.", item.name());
+                }
+                Err(e) => {
+                    println!("{e}");
+                    loot.ground.push(key);
+                }
+            }
+        }
+    }
+
+    /// opens the chest in this zone. locked chests use up a key.
+    fn open(&self, player: &mut Player, world: &mut World) {
+        let chest = world.levels[self.level][self.coords()]
+            .loot
+            .as_mut()
+            .filter(|loot| loot.chest.is_some());
+
+        let Some(loot) = chest else {
+            println!("there is nothing here to open.");
+            return;
+        };
+
+        let contents = loot.open(|| {
+            player
+                .inventory
+                .find(loot::CHEST_KEY)
+                .is_some_and(|key| player.inventory.remove(&key, 1).is_ok())
+        });
+
+        match contents {
+            Some(items) if items.is_empty() => println!("the chest is empty."),
+            Some(items) => {
+                let items: Vec<String> = items.iter().map(|key| key.to_string()).collect();
+                println!("you open the chest and find: {}.", items.join(", "));
+            }
+            None => println!(
+                "the chest is locked. you need a {} to open it.",
+                loot::CHEST_KEY
+            ),
+        }
+    }
+
+    /// starts trading with the shop in this zone.
+    fn shop(&mut self, player: &Player, world: &mut World) {
+        match ShopState::new(self.level, self.coords(), player, world) {
+            Some(shop) => self.next = Some(Rc::new(RefCell::new(shop))),
+            None => println!("there is no one here to trade with."),
+        }
+    }
+
+    /// hires the companion waiting in this zone.
+    fn recruit(&self, player: &mut Player, world: &mut World) {
+        let zone = &mut world.levels[self.level][self.coords()];
+
+        let Some(hireling) = zone.companion else {
+            println!("there is no one here to recruit.");
+            return;
+        };
+
+        if player.party.len() >= MAX_PARTY {
+            println!("your party is full. dismiss someone first.");
+            return;
+        }
+
+        zone.companion = None;
+        player.party.push(Box::new(Companion::hire(hireling)));
+        println!("the {} joins your party!", hireling.name);
+    }
+
+    /// lists the players party.
+    fn party(&self, player: &Player) {
+        if player.party.is_empty() {
+            println!("you travel alone.");
+            return;
+        }
+
+        for (i, ally) in player.party.iter().enumerate() {
+            println!(
+                "{}) {}: {}/{} hp",
+                i + 1,
+                ally.get_name(),
+                ally.get_hp(),
+                ally.get_max_hp()
+            );
+        }
+    }
+
+    /// sends a member of the players party away. `n` is the number shown by the party command.
+    fn dismiss(&self, n: Option<&str>, player: &mut Player) {
+        match n.and_then(|n| n.parse::<usize>().ok()) {
+            Some(n) if n > 0 && n <= player.party.len() => {
+                let ally = player.party.remove(n - 1);
+                println!("{} leaves your party.", ally.get_name());
+            }
+            _ => println!("dismiss who? (see party)"),
+        }
+    }
+
+    fn walk(&mut self, dir: (isize, isize), player: &mut Player, world: &mut World) {
+        match world.levels[self.level].step(self.coords(), dir) {
+            Some(next) => {
+                self.enter(next, player, world);
+                self.look(world);
+
+                if !self.ambush(world) {
+                    self.check_portal(player, world);
+                }
+            }
+            None => println!("you can't go that way."),
+        }
+    }
+
+    /// warns the player about the boss room if they are standing on the boss portal. the player
+    /// must answer `yes` before they are sent into the lair.
+    fn check_portal(&mut self, player: &Player, world: &World) {
+        let level = &world.levels[self.level];
+
+        if self.coords() != (level.boss_portal.0 as usize, level.boss_portal.1 as usize) {
+            return;
+        }
+
+        let room = &level.boss_room;
+
+        let Some(boss) = &room.boss else {
+            println!("the portal is dark. {} lies empty.", room.name);
+            return;
+        };
+
+        let boss_power = boss.get_stats().power();
+
+        println!("beyond the portal lies {}.", room.name);
+        println!("{}", room.welcome);
+        println!("{}: {}", boss.get_name(), boss.get_stats());
+        println!(
+            "recommended power level: {}, yours is {}.",
+            boss_power * 3 / 4,
+            player.get_stats().power()
+        );
+        println!("WARNING: there may be no escape once inside. enter the portal? (yes/no)");
+
+        self.confirm_portal = true;
+    }
+
+    /// puts the key of the levels beaten boss on the players key ring.
+    fn claim(&self, player: &mut Player, world: &World) {
+        let key = match BiomeKey::earn(&world.levels[self.level], &player.name) {
+            Ok(key) => key,
+            Err(e) => {
+                println!("{e}");
+                return;
+            }
+        };
+
+        match player.keys.add(key.clone(), &player.name) {
+            Ok(true) => println!(
+                "the {key} is yours! ({}/{} keys)",
+                player.keys.count(),
+                Biome::ALL.len()
+            ),
+            Ok(false) => println!("you already hold the {key}."),
+            Err(e) => println!("{e}"),
+        }
+
+        if player.keys.complete() {
+            println!("all seven keys are yours. find a sanctuary and unlock the gate.");
+        }
+    }
+
+    /// opens the gate with the seven biome keys, ending the game. the gate stands in every
+    /// Sanctuary.
+    fn unlock(&mut self, player: &mut Player, world: &World) {
+        if world.levels[self.level][self.coords()].feild_type != ZoneType::Sanctuary {
+            println!("there is nothing here to unlock.");
+            return;
+        }
+
+        if player.keys.opened {
+            println!("the gate stands open. your name is already on the wall.");
+            return;
+        }
+
+        match player.keys.open(&player.name) {
+            Ok(()) => {
+                println!("you fit the seven keys into the gate. (press enter to go on)");
+                self.next = Some(Rc::new(RefCell::new(GateState::default())));
+            }
+            Err(e) => println!("{e}"),
+        }
+    }
+
+    /// sends the player through the boss portal into the lair.
+    fn enter_lair(&mut self, world: &mut World) {
+        let room = &mut world.levels[self.level].boss_room;
+
+        let Some(boss) = room.boss.take() else {
+            return;
+        };
+
+        println!("{}", room.desc);
+
+        let name = boss.get_name();
+        let mut battle = BattleState::new(vec![boss]);
+        battle.escapable = false;
+        battle.reserves = room.minions.take().unwrap_or_default();
+
+        let battle = Rc::new(RefCell::new(battle));
+        self.lair = Some((name, battle.clone()));
+        self.next = Some(battle);
+    }
+
+    /// fast travels to a visited Sanctuary or Shelter. `place` is either the number shown by a
+    /// bare `travel` or the name of the zone. the route is walked one zone at a time so travel
+    /// costs as much time as walking it would.
+    fn travel(&mut self, place: &str, player: &mut Player, world: &mut World) {
+        let level = &world.levels[self.level];
+        let stops = waypoints(level);
+
+        if place.is_empty() {
+            if stops.is_empty() {
+                println!("you haven't found anywhere to travel to yet.");
+            }
+
+            for (i, stop) in stops.iter().enumerate() {
+                println!("{}) {} {:?}", i + 1, level[*stop].title(), stop);
+            }
+
+            return;
+        }
+
+        let dest = match place.parse::<usize>() {
+            Ok(n) => stops.get(n.wrapping_sub(1)).copied(),
+            Err(_) => stops
+                .iter()
+                .find(|stop| level[**stop].title().eq_ignore_ascii_case(place))
+                .copied(),
+        };
+
+        let Some(dest) = dest else {
+            println!("you haven't found anywhere called \"{place}\" to travel to.");
+            return;
+        };
+
+        let Some(route) = level.find_path(self.coords(), dest) else {
+            println!("there is no way to get there from here.");
+            return;
+        };
+
+        println!(
+            "you set off for {}, {} zones away.",
+            level[dest].title(),
+            route.len()
+        );
+
+        for next in route {
+            self.enter(next, player, world);
+
+            if self.ambush(world) {
+                return;
+            }
+        }
+
+        self.look(world);
+        self.check_portal(player, world);
     }
 }
 
@@ -30,12 +725,176 @@ impl GameState for ExploreState {
         false
     }
 
-    #[allow(unused_variables)]
-    fn step(&mut self, cmd: &str, player: &Player) -> bool {
-        // TODO: parse cmd and do the thing the player instructed
+    fn step(&mut self, cmd: &str, player: &mut Player, world: &mut World) -> bool {
+        let mut args = cmd.split_whitespace();
+        let confirm_portal = std::mem::take(&mut self.confirm_portal);
+
+        match args.next() {
+            Some("yes" | "y") if confirm_portal => self.enter_lair(world),
+            Some("no") if confirm_portal => println!("you step back from the portal."),
+            Some("go") => match args.next().and_then(direction) {
+                Some(dir) => self.walk(dir, player, world),
+                None => println!("go where? (north, south, east, or west)"),
+            },
+            Some("look") => self.describe(world),
+            Some("read") => match args.collect::<Vec<_>>().join(" ") {
+                what if names_sign(&what) => {
+                    for line in self.read_sign(world) {
+                        println!("{line}");
+                    }
+                }
+                item => self.use_item(&item, player),
+            },
+            Some("study") => self.study(player, world),
+            Some("stats") => {
+                for line in player.stat_sheet() {
+                    println!("{line}");
+                }
+            }
+            Some("inventory" | "inv" | "i") => {
+                for line in player.inventory.listing() {
+                    println!("{line}");
+                }
+            }
+            Some("use") => self.use_item(&args.collect::<Vec<_>>().join(" "), player),
+            Some("gear") => {
+                for line in player.gear_sheet() {
+                    println!("{line}");
+                }
+            }
+            Some("compare") => match player.compare(&args.collect::<Vec<_>>().join(" ")) {
+                Ok(lines) => lines.iter().for_each(|line| println!("{line}")),
+                Err(e) => println!("{e}"),
+            },
+            Some("equip") => match player.equip(&args.collect::<Vec<_>>().join(" ")) {
+                Ok(lines) => lines.iter().for_each(|line| println!("{line}")),
+                Err(e) => println!("{e}"),
+            },
+            Some("unequip") => match player.unequip(&args.collect::<Vec<_>>().join(" ")) {
+                Ok(line) => println!("{line}"),
+                Err(e) => println!("{e}"),
+            },
+            Some("keys") => {
+                for line in player.keys.listing() {
+                    println!("{line}");
+                }
+            }
+            Some("unlock") => self.unlock(player, world),
+            Some("score") => {
+                for line in player.card.breakdown() {
+                    println!("{line}");
+                }
+            }
+            Some("fame") => {
+                for line in world.fame.listing() {
+                    println!("{line}");
+                }
+            }
+            Some("recipes") => self.recipes(player),
+            Some("craft") => self.craft(&args.collect::<Vec<_>>().join(" "), player, world),
+            Some("search") => self.search(player, world),
+            Some("take" | "get") => self.take(&args.collect::<Vec<_>>().join(" "), player, world),
+            Some("open") => self.open(player, world),
+            Some("rest") => self.rest(player, world),
+            Some("train") => match player.train(args.next().unwrap_or_default()) {
+                Ok(()) => println!("you train hard. you have {} points left.", player.points),
+                Err(e) => println!("{e}"),
+            },
+            Some("shop" | "trade") => self.shop(player, world),
+            Some("recruit") => self.recruit(player, world),
+            Some("party") => self.party(player),
+            Some("dismiss") => self.dismiss(args.next(), player),
+            Some("travel") => self.travel(&args.collect::<Vec<_>>().join(" "), player, world),
+            Some(cmd) => match direction(cmd) {
+                Some(dir) => self.walk(dir, player, world),
+                None => println!("unknown command \"{cmd}\""),
+            },
+            None => {}
+        }
 
         self.is_done()
     }
+
+    fn next_state(&mut self) -> Option<Rc<RefCell<dyn GameState>>> {
+        self.next.take()
+    }
+
+    fn resume(&mut self, player: &mut Player, world: &mut World) {
+        let mut outcome = None;
+
+        if let Some((loc, battle)) = self.encounter.take() {
+            let zone = &mut world.levels[self.level][loc];
+            outcome = battle.borrow().outcome;
+
+            match (&mut zone.enemies, outcome) {
+                (Some(spawner), Some(BattleOutcome::Victory)) => spawner.clear(world.clock),
+                // the enemies that are left stay in the zone, waiting for the player.
+                (Some(spawner), _) => spawner.survivors(&battle.borrow().enemies),
+                (None, _) => {}
+            }
+
+            // whatever the player had no room for is left where the battle was.
+            zone.loot
+                .get_or_insert_with(Stash::default)
+                .ground
+                .append(&mut battle.borrow_mut().spoils);
+
+            if let (Some(prev), Some(BattleOutcome::Fled)) = (self.prev, outcome) {
+                self.loc = prev;
+                println!("you run back the way you came.");
+                self.look(world);
+            }
+        }
+
+        if let Some((boss, battle)) = self.lair.take() {
+            let room = &mut world.levels[self.level].boss_room;
+            let mut battle = battle.borrow_mut();
+            outcome = battle.outcome;
+
+            // anyone still standing who isn't the boss is a minion, they go back to the lair too.
+            let (bosses, mut minions): (Vec<_>, Vec<_>) = battle
+                .enemies
+                .drain(..)
+                .partition(|enemy| enemy.get_name() == boss);
+            minions.append(&mut battle.reserves);
+
+            let slain = match bosses.into_iter().next() {
+                // the boss survived, it waits for the players return.
+                Some(boss) => {
+                    room.set_boss(boss);
+                    false
+                }
+                None => {
+                    room.kill_boss();
+                    true
+                }
+            };
+
+            room.set_minions((!minions.is_empty()).then_some(minions));
+
+            // the lair is left behind the player, so anything they couldn't carry is left by the
+            // portal.
+            world.levels[self.level][self.coords()]
+                .loot
+                .get_or_insert_with(Stash::default)
+                .ground
+                .append(&mut battle.spoils);
+
+            if slain {
+                player.card.boss();
+                self.claim(player, world);
+            }
+
+            if outcome == Some(BattleOutcome::Fled) {
+                println!("you stumble back through the portal.");
+                self.look(world);
+            }
+        }
+
+        if outcome == Some(BattleOutcome::Defeat) {
+            self.revive(player, world);
+        }
+    }
 }
 
 pub struct StateStack {
@@ -43,9 +902,9 @@ pub struct StateStack {
 }
 
 impl StateStack {
-    pub fn new() -> Self {
+    pub fn new(player: &mut Player, world: &mut World) -> Self {
         let mut stack: Vec<Rc<RefCell<dyn GameState>>> = Vec::with_capacity(3);
-        stack.push(Rc::from(RefCell::from(ExploreState::new())));
+        stack.push(Rc::from(RefCell::from(ExploreState::new(player, world))));
 
         Self { stack }
     }
@@ -64,10 +923,26 @@ impl StateStack {
         }
     }
 
-    pub fn step(&mut self, cmd: &str, player: &Player) -> bool {
-        match self.stack.last() {
-            Some(state) => state.borrow_mut().step(cmd, player),
-            None => false,
+    /// steps the active state, then pushes any state it spawned. returns true when the active
+    /// state is ready to exit.
+    pub fn step(&mut self, cmd: &str, player: &mut Player, world: &mut World) -> bool {
+        let Some(state) = self.stack.last().cloned() else {
+            return false;
+        };
+
+        let done = state.borrow_mut().step(cmd, player, world);
+
+        if let Some(next) = state.borrow_mut().next_state() {
+            self.stack.push(next);
+        }
+
+        done
+    }
+
+    /// lets the active state know that the state above it was popped.
+    pub fn resume(&mut self, player: &mut Player, world: &mut World) {
+        if let Some(state) = self.stack.last() {
+            state.borrow_mut().resume(player, world);
         }
     }
 }
@@ -80,17 +955,89 @@ pub struct Game {
 }
 
 impl Game {
-    pub fn new() -> Self {
-        Self {
-            world: World::new(),
-            player: Player::new(None),
-            state: StateStack::new(), // Box::new(ExploreState::new()),
-        }
+    /// generates the world and drops the player at its warp in.
+    pub fn new() -> anyhow::Result<Self> {
+        let mut world = World::new();
+        world.generate()?;
+        let mut player = Player::new(None);
+        let state = StateStack::new(&mut player, &mut world);
+
+        Ok(Self {
+            world,
+            player,
+            state,
+        })
     }
 
     pub fn step(&mut self, cmd: &str) {
-        if self.state.step(cmd, &self.player) {
+        if self.state.step(cmd, &mut self.player, &mut self.world) {
             self.state.pop();
+            self.state.resume(&mut self.player, &mut self.world);
+        }
+
+        self.player.score = self.player.card.total();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zone::{BossRoom, Zone};
+
+    /// a world with one 3 by 3 level of open paths, warping in to a Sanctuary in the middle.
+    fn world() -> World {
+        let zones = (0..3)
+            .map(|_| {
+                (0..3)
+                    .map(|_| {
+                        let mut zone = Zone::new("a path");
+                        zone.feild_type = ZoneType::OpenPath;
+                        zone
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut level = Level {
+            zones,
+            dim: (3, 3),
+            warp_in: (1, 1),
+            boss_portal: (2, 2),
+            boss_room: BossRoom::new(None),
+            biome: Biome::Forest,
+            seed: 7,
+        };
+        level[(1, 1)].feild_type = ZoneType::Sanctuary;
+        level[(0, 0)].feild_type = ZoneType::Temple;
+
+        let mut world = World::new();
+        world.levels.push(level);
+
+        world
+    }
+
+    #[test]
+    fn read_sign_in_a_sanctuary() {
+        let mut world = world();
+        let mut player = Player::new(Some("Tester"));
+        let mut state = ExploreState::new(&mut player, &mut world);
+
+        for what in ["", "sign", "Sign", "SIGN"] {
+            assert!(names_sign(what), "read {what:?} should read the sign");
         }
+
+        assert!(!names_sign("old notes"));
+
+        let sign = state.read_sign(&world);
+        assert_eq!(sign, lore::sign(&world.levels[0], (1, 1)));
+        assert_eq!(sign[0], "Welcome, traveler, to the Forest.");
+
+        // reading the sign costs nothing and doesn't touch the pack.
+        let held = player.inventory.listing();
+        state.step("read sign", &mut player, &mut world);
+        assert_eq!(player.inventory.listing(), held);
+        assert_eq!(state.coords(), (1, 1));
+
+        state.loc = (0, 0);
+        assert_eq!(state.read_sign(&world), ["there is no sign here."]);
     }
 }
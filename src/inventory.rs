@@ -0,0 +1,241 @@
+use crate::items::{self, Category, Item, ItemKey};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// how many different items the player can carry when they start out.
+pub const INVENTORY_SLOTS: u8 = 20;
+
+/// how an Inventory is saved. (slots, [(name, inst, count)])
+type Packed = (u8, Vec<(Arc<str>, u8, u8)>);
+
+/// everything the player is carrying. every item in the game is in here, most of them just have a
+/// count of zero. stackable items all share the ItemKey with an inst of 0, so a stack holds up to
+/// 255. everything else gets its own inst (and its own slot), so there can be up to 256 of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "Packed", into = "Packed")]
+pub struct Inventory {
+    /// how many items are being carried, by key. anything not in here has a count of zero.
+    counts: HashMap<ItemKey, u8>,
+    /// how many different items (stacks count as one) can be carried at once.
+    pub slots: u8,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self {
+            counts: HashMap::new(),
+            slots: INVENTORY_SLOTS,
+        }
+    }
+
+    /// how many of an item are being carried.
+    pub fn count(&self, key: &ItemKey) -> u8 {
+        self.counts.get(key).copied().unwrap_or(0)
+    }
+
+    /// how many slots are in use.
+    pub fn used(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// puts an item in the inventory. returns the key it was stored under, stackable items
+    /// share one key and everything else gets its own.
+    pub fn add(&mut self, item: &dyn Item) -> Result<ItemKey> {
+        let key = if item.stackable() {
+            ItemKey::new(&item.name())
+        } else {
+            let Some(inst) = (0..=u8::MAX).find(|inst| {
+                !self.counts.contains_key(&ItemKey {
+                    name: item.name(),
+                    inst: *inst,
+                })
+            }) else {
+                bail!("you can't carry any more {}.", item.name());
+            };
+
+            ItemKey {
+                name: item.name(),
+                inst,
+            }
+        };
+
+        let full = self.used() >= self.slots as usize;
+
+        match self.counts.get_mut(&key) {
+            Some(&mut u8::MAX) => bail!("you can't carry any more {}.", item.name()),
+            Some(count) => *count += 1,
+            None if full => {
+                bail!(
+                    "your pack is full. there is no room for the {}.",
+                    item.name()
+                )
+            }
+            None => {
+                self.counts.insert(key.clone(), 1);
+            }
+        }
+
+        Ok(key)
+    }
+
+    /// takes `n` of an item out of the inventory.
+    pub fn remove(&mut self, key: &ItemKey, n: u8) -> Result<()> {
+        match self.counts.get_mut(key) {
+            Some(count) if *count > n => *count -= n,
+            Some(count) if *count == n => {
+                self.counts.remove(key);
+            }
+            _ => bail!("you don't have {n} {}.", key.name),
+        }
+
+        Ok(())
+    }
+
+    /// puts an item back in the inventory under its own key, so it keeps its inst. (eg, gear
+    /// being taken off) if something else has taken that key in the meantime, it's stored the
+    /// same way as add.
+    pub fn put_back(&mut self, item: &dyn Item) -> Result<ItemKey> {
+        let key = item.key();
+
+        if item.stackable() || self.counts.contains_key(&key) {
+            return self.add(item);
+        }
+
+        if self.used() >= self.slots as usize {
+            bail!(
+                "your pack is full. there is no room for the {}.",
+                item.name()
+            );
+        }
+
+        self.counts.insert(key.clone(), 1);
+
+        Ok(key)
+    }
+
+    /// takes `n` of an item out of the inventory by name, ignoring case, from as many instances
+    /// of it as it takes. the first ones picked up go first. if there aren't `n` of them, nothing
+    /// is taken.
+    pub fn remove_named(&mut self, name: &str, n: u8) -> Result<()> {
+        let mut keys: Vec<ItemKey> = self
+            .counts
+            .keys()
+            .filter(|key| key.name.eq_ignore_ascii_case(name))
+            .cloned()
+            .collect();
+        keys.sort_by_key(|key| key.inst);
+
+        let have = keys
+            .iter()
+            .fold(0u8, |total, key| total.saturating_add(self.count(key)));
+
+        if have < n {
+            bail!("you don't have {n} {name}.");
+        }
+
+        let mut left = n;
+
+        for key in keys {
+            let taken = left.min(self.count(&key));
+            self.remove(&key, taken)?;
+            left -= taken;
+
+            if left == 0 {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// takes one of an item out of the inventory to use it.
+    pub fn take(&mut self, key: &ItemKey) -> Option<Box<dyn Item>> {
+        let item = items::make(key)?;
+        self.remove(key, 1).ok()?;
+
+        Some(item)
+    }
+
+    /// finds an item being carried by name, ignoring case. if there are several, the first one
+    /// picked up is returned.
+    pub fn find(&self, name: &str) -> Option<ItemKey> {
+        self.counts
+            .keys()
+            .filter(|key| key.name.eq_ignore_ascii_case(name))
+            .min_by_key(|key| key.inst)
+            .cloned()
+    }
+
+    /// everything being carried as (key, count, category), sorted by category and then by name.
+    pub fn sorted(&self) -> Vec<(ItemKey, u8, Category)> {
+        let mut items: Vec<_> = self
+            .counts
+            .iter()
+            .filter_map(|(key, count)| {
+                items::lookup(&key.name).map(|def| (key.clone(), *count, def.category))
+            })
+            .collect();
+
+        items.sort_by(|a, b| (a.2, &a.0.name, a.0.inst).cmp(&(b.2, &b.0.name, b.0.inst)));
+
+        items
+    }
+
+    /// the inventory as the player sees it, one line per item, grouped by category.
+    pub fn listing(&self) -> Vec<String> {
+        let mut lines = vec![format!("your pack ({}/{} slots):", self.used(), self.slots)];
+        let mut last = None;
+
+        if self.counts.is_empty() {
+            lines.push("  nothing but dust.".to_string());
+        }
+
+        for (key, count, category) in self.sorted() {
+            if last != Some(category) {
+                lines.push(format!("{category}:"));
+                last = Some(category);
+            }
+
+            match count {
+                1 => lines.push(format!("  {key}")),
+                n => lines.push(format!("  {key} x{n}")),
+            }
+        }
+
+        lines
+    }
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Packed> for Inventory {
+    fn from((slots, items): Packed) -> Self {
+        Self {
+            counts: items
+                .into_iter()
+                .filter(|(_, _, count)| *count > 0)
+                .map(|(name, inst, count)| (ItemKey { name, inst }, count))
+                .collect(),
+            slots,
+        }
+    }
+}
+
+impl From<Inventory> for Packed {
+    fn from(inventory: Inventory) -> Self {
+        (
+            inventory.slots,
+            inventory
+                .counts
+                .into_iter()
+                .map(|(key, count)| (key.name, key.inst, count))
+                .collect(),
+        )
+    }
+}
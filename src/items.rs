@@ -1,16 +1,630 @@
-use serde::{Deserialize, Serialize};
+use crate::battle_logic::Effect;
+use crate::damage::{self, Damage, DamageType};
+use crate::level::Biome;
+use crate::player::{Buff, BuffType, EquipType, Equipment, Stats};
+use crate::spells::{self, TargetMode};
+use crate::status::{Status, StatusKind};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 use std::sync::Arc;
 
+/// what sort of thing an item is.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    /// used up when used. (potions, scrolls, etc)
+    Consumable,
+    /// worn or held.
+    Equipment,
+    /// opens the way to somewhere.
+    Key,
+    /// wanted by someone, can't be sold.
+    Quest,
+    /// crafted into other things.
+    Material,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Category::Consumable => write!(f, "consumable"),
+            Category::Equipment => write!(f, "equipment"),
+            Category::Key => write!(f, "key"),
+            Category::Quest => write!(f, "quest item"),
+            Category::Material => write!(f, "material"),
+        }
+    }
+}
+
+/// anything the player can pick up. items are identified by their ItemKey, which is also what
+/// gets sent when an item is serialized. the rest of the item is looked back up in the catalog
+/// on the other end.
 pub trait Item {
-    // TODO: write Item trait
+    /// the items identity. two items with the same key are the same item.
+    fn key(&self) -> ItemKey;
+
+    /// what the item is called. (eg, "Potion", "Iron Sword", etc)
+    fn name(&self) -> Arc<str>;
+
+    /// a line or two about the item, shown when the player examines it.
+    fn desc(&self) -> Arc<str>;
+
+    fn category(&self) -> Category;
+
+    /// true if several of the item can share one inventory slot.
+    fn stackable(&self) -> bool {
+        matches!(self.category(), Category::Consumable | Category::Material)
+    }
+
+    /// what the item is worth, in gold.
+    fn value(&self) -> u16;
+
+    /// who the item can be used on.
+    fn target_mode(&self) -> TargetMode {
+        TargetMode::TheSelf
+    }
+
+    /// uses the item on a target. items that hit more than one target are used once per target.
+    /// returns None if the item can't be used like that.
+    #[allow(unused_variables)]
+    fn use_on(&mut self, user: &Stats, target: &Stats) -> Option<Effect> {
+        None
+    }
+
+    /// what the item becomes when it's worn or held, if it can be.
+    fn equipment(&self) -> Option<Equipment> {
+        None
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+impl Serialize for dyn Item {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.key().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Box<dyn Item> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = ItemKey::deserialize(deserializer)?;
+
+        make(&key).ok_or_else(|| de::Error::custom(format!("no item called \"{}\"", key.name)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct ItemKey {
     // level: u8,
     // loc: (u8, u8),
-    name: Arc<str>,
+    pub name: Arc<str>,
     /// gives a max of 256 for each item
-    inst: u8,
+    pub inst: u8,
     // maker_adr: I2cAdr,
 }
+
+impl ItemKey {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: Arc::from(name),
+            inst: 0,
+        }
+    }
+}
+
+impl fmt::Display for ItemKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.inst {
+            0 => write!(f, "{}", self.name),
+            inst => write!(f, "{} #{inst}", self.name),
+        }
+    }
+}
+
+/// what an item in the catalog does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Use {
+    /// heals the target
+    Heal(u8),
+    /// puts a status effect on the target. (kind, turns, potency)
+    Status(StatusKind, u8, u8),
+    /// casts a spell from the spell book, for free.
+    Spell(&'static str),
+    /// deals magical damage to every enemy.
+    Bomb(u8),
+    /// gets the user out of the battle, even one there is no running from.
+    Escape,
+    /// worn in a slot, adding to each stat. ([hp, str, mg_str, def, mg_def, speed])
+    Gear(EquipType, [i8; 6]),
+    /// teaches a crafting recipe the reader doesn't know yet. (see crafting)
+    Recipe,
+    /// does nothing on its own.
+    Nothing,
+}
+
+/// one item in the catalog.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ItemDef {
+    pub name: &'static str,
+    pub desc: &'static str,
+    pub category: Category,
+    /// what the item is worth, in gold.
+    pub value: u16,
+    pub does: Use,
+}
+
+const fn item(
+    name: &'static str,
+    category: Category,
+    value: u16,
+    does: Use,
+    desc: &'static str,
+) -> ItemDef {
+    ItemDef {
+        name,
+        desc,
+        category,
+        value,
+        does,
+    }
+}
+
+const fn consumable(name: &'static str, value: u16, does: Use, desc: &'static str) -> ItemDef {
+    item(name, Category::Consumable, value, does, desc)
+}
+
+const fn gear(
+    name: &'static str,
+    slot: EquipType,
+    stats: [i8; 6],
+    value: u16,
+    desc: &'static str,
+) -> ItemDef {
+    item(
+        name,
+        Category::Equipment,
+        value,
+        Use::Gear(slot, stats),
+        desc,
+    )
+}
+
+const fn material(name: &'static str, value: u16, desc: &'static str) -> ItemDef {
+    item(name, Category::Material, value, Use::Nothing, desc)
+}
+
+const fn key(name: &'static str, desc: &'static str) -> ItemDef {
+    item(name, Category::Key, 0, Use::Nothing, desc)
+}
+
+// consumable(name, value, does, desc)
+// gear(name, slot, [hp, str, mg_str, def, mg_def, speed], value, desc)
+// material(name, value, desc)
+// key(name, desc)
+const CATALOG: [ItemDef; 60] = [
+    // potions
+    consumable(
+        "Potion",
+        15,
+        Use::Heal(10),
+        "A small red vial. Restores 10 hp.",
+    ),
+    consumable(
+        "Greater Potion",
+        40,
+        Use::Heal(25),
+        "A large red flask. Restores 25 hp.",
+    ),
+    consumable(
+        "Regen Draught",
+        30,
+        Use::Status(StatusKind::Regen, 4, 3),
+        "A bitter green brew that knits wounds over time.",
+    ),
+    consumable(
+        "Haste Tonic",
+        35,
+        Use::Status(StatusKind::Haste, 3, 0),
+        "Fizzes on the tongue. Everything else seems to slow down.",
+    ),
+    consumable(
+        "Stoneskin Tonic",
+        35,
+        Use::Status(StatusKind::Shield, 3, 4),
+        "Leaves your skin cold and hard as granite for a while.",
+    ),
+    // scrolls
+    consumable(
+        "Scroll of Fireball",
+        45,
+        Use::Spell("Fireball"),
+        "Read it aloud and every enemy burns.",
+    ),
+    consumable(
+        "Scroll of Healing",
+        30,
+        Use::Spell("Heal"),
+        "A prayer of mending, written in gold ink.",
+    ),
+    consumable(
+        "Scroll of Calling",
+        40,
+        Use::Spell("Call Wolf"),
+        "A howl is scrawled across the page.",
+    ),
+    consumable(
+        "Elixir",
+        60,
+        Use::Heal(40),
+        "Thick and golden. Restores 40 hp.",
+    ),
+    consumable(
+        "Crafting Notes",
+        30,
+        Use::Recipe,
+        "Someone's scribbled notes on how to make something.",
+    ),
+    consumable(
+        "Firebomb",
+        25,
+        Use::Bomb(8),
+        "A clay pot full of oil and a short fuse.",
+    ),
+    consumable(
+        "Smoke Bomb",
+        30,
+        Use::Escape,
+        "Throw it down and slip away in the smoke. Works on anything.",
+    ),
+    // weapons
+    gear(
+        "Rusty Sword",
+        EquipType::WeaponHand,
+        [0, 2, 0, 0, 0, 0],
+        10,
+        "It has seen better days.",
+    ),
+    gear(
+        "Iron Sword",
+        EquipType::WeaponHand,
+        [0, 4, 0, 0, 0, 0],
+        60,
+        "A plain, reliable blade.",
+    ),
+    gear(
+        "War Axe",
+        EquipType::TwoHanded,
+        [0, 6, 0, 0, 0, -1],
+        110,
+        "Heavy, slow, and very final.",
+    ),
+    gear(
+        "Oak Staff",
+        EquipType::TwoHanded,
+        [0, 1, 3, 0, 1, 0],
+        55,
+        "Carved with runes that hum.",
+    ),
+    gear(
+        "Buckler",
+        EquipType::OffHand,
+        [0, 0, 0, 2, 0, 0],
+        30,
+        "A small round shield.",
+    ),
+    gear(
+        "Spell Tome",
+        EquipType::OffHand,
+        [0, 0, 2, 0, 1, 0],
+        50,
+        "Full of someone else's notes.",
+    ),
+    // armor
+    gear(
+        "Leather Cap",
+        EquipType::Helmet,
+        [0, 0, 0, 1, 0, 0],
+        15,
+        "Better than nothing.",
+    ),
+    gear(
+        "Iron Helm",
+        EquipType::Helmet,
+        [2, 0, 0, 2, 0, 0],
+        50,
+        "Dented, but sound.",
+    ),
+    gear(
+        "Leather Armor",
+        EquipType::Body,
+        [0, 0, 0, 2, 1, 0],
+        40,
+        "Supple and quiet.",
+    ),
+    gear(
+        "Chainmail",
+        EquipType::Body,
+        [4, 0, 0, 4, 0, -1],
+        90,
+        "Heavy rings of riveted steel.",
+    ),
+    gear(
+        "Leather Leggings",
+        EquipType::Pants,
+        [0, 0, 0, 1, 0, 1],
+        25,
+        "Made for walking.",
+    ),
+    gear(
+        "Gauntlets",
+        EquipType::Hands,
+        [0, 1, 0, 1, 0, 0],
+        35,
+        "Iron knuckles under leather.",
+    ),
+    gear(
+        "Mage Robe",
+        EquipType::FullBody,
+        [0, 0, 3, 1, 3, 0],
+        120,
+        "Embroidered with wards.",
+    ),
+    gear(
+        "Amulet of Warding",
+        EquipType::Pendant,
+        [0, 0, 0, 0, 3, 0],
+        70,
+        "A stone that is always cold.",
+    ),
+    gear(
+        "Ring of Swiftness",
+        EquipType::Ring,
+        [0, 0, 0, 0, 0, 2],
+        80,
+        "Your fingers twitch.",
+    ),
+    // boss drops, one of each.
+    gear(
+        "Heartwood Staff",
+        EquipType::TwoHanded,
+        [0, 2, 6, 0, 3, 0],
+        300,
+        "Still growing.",
+    ),
+    gear(
+        "Oracle's Eye",
+        EquipType::Pendant,
+        [0, 0, 4, 0, 4, 0],
+        300,
+        "It sees what is coming.",
+    ),
+    gear(
+        "Wyrmscale Mail",
+        EquipType::Body,
+        [6, 0, 0, 6, 2, 0],
+        300,
+        "Each scale is hot to touch.",
+    ),
+    gear(
+        "Frostbrand",
+        EquipType::WeaponHand,
+        [0, 7, 2, 0, 0, 0],
+        300,
+        "Frost creeps up the blade.",
+    ),
+    gear(
+        "Crown of the Forgotten King",
+        EquipType::Helmet,
+        [4, 2, 2, 3, 3, 0],
+        300,
+        "Heavy.",
+    ),
+    gear(
+        "Warden's Shackle",
+        EquipType::Hands,
+        [0, 3, 0, 3, 0, 1],
+        300,
+        "A broken cuff, still locked.",
+    ),
+    gear(
+        "Guild Master's Signet",
+        EquipType::Ring,
+        [0, 2, 0, 0, 0, 3],
+        300,
+        "Opens many doors.",
+    ),
+    // crafted gear, see crafting.
+    gear(
+        "Pelt Cloak",
+        EquipType::Body,
+        [3, 0, 0, 2, 1, 0],
+        45,
+        "Stitched wolf pelts. Warm, and it smells like it.",
+    ),
+    gear(
+        "Stinger Dagger",
+        EquipType::OffHand,
+        [0, 2, 0, 0, 0, 1],
+        40,
+        "A scorpion's sting on a bone handle.",
+    ),
+    gear(
+        "Feathered Cap",
+        EquipType::Helmet,
+        [0, 0, 0, 1, 0, 2],
+        40,
+        "Harpy feathers make you feel lighter on your feet.",
+    ),
+    gear(
+        "Bone Charm",
+        EquipType::Pendant,
+        [0, 0, 2, 0, 2, 0],
+        55,
+        "Rattles softly when spirits are near.",
+    ),
+    gear(
+        "Frost Band",
+        EquipType::Ring,
+        [0, 0, 2, 0, 1, 1],
+        60,
+        "Cold enough to burn.",
+    ),
+    // materials
+    material("Wolf Pelt", 8, "Thick grey fur."),
+    material("Iron Ore", 12, "A lump of rust-red rock."),
+    material("Healing Herb", 5, "Smells of mint and wet earth."),
+    material("Dryad Sap", 10, "Sticky, golden, and faintly glowing."),
+    material("Holy Ash", 10, "Grey ash from a temple censer."),
+    material("Ectoplasm", 14, "Cold, and it doesn't stay still."),
+    material("Scorpion Stinger", 10, "Still dripping."),
+    material("Harpy Feather", 8, "Long, oily, and surprisingly strong."),
+    material("Frost Shard", 14, "A splinter of ice that never melts."),
+    material("Bone Dust", 6, "Ground from something that used to walk."),
+    material("Slime Gel", 6, "Wobbles when you aren't looking at it."),
+    material(
+        "Cloth Scraps",
+        4,
+        "Torn from someone's coat. Possibly yours.",
+    ),
+    // keys, one for each biome. bosses guard them.
+    key(
+        "Forest Key",
+        "A key of living wood, still putting out leaves.",
+    ),
+    key("Temple Key", "A key of white marble, warm to the touch."),
+    key(
+        "Desert Key",
+        "A key of fused glass with sand trapped inside.",
+    ),
+    key("Mountain Key", "A key of blue ice that never melts."),
+    key("Ruins Key", "A key of crumbling bronze."),
+    key("Dungeon Key", "A heavy iron key on a ring of rusted chain."),
+    key("Town Key", "A brass key stamped with the guild's seal."),
+    // opens locked chests, see loot::Chest.
+    item(
+        "Rusty Key",
+        Category::Key,
+        20,
+        Use::Nothing,
+        "Opens old chests. Snaps off in the lock afterwards.",
+    ),
+    // quest items
+    item(
+        "Sealed Letter",
+        Category::Quest,
+        0,
+        Use::Nothing,
+        "Addressed to someone you've never heard of.",
+    ),
+];
+
+/// every item in the game.
+pub fn catalog() -> &'static [ItemDef] {
+    &CATALOG
+}
+
+/// finds an item in the catalog by name, ignoring case.
+pub fn lookup(name: &str) -> Option<&'static ItemDef> {
+    CATALOG
+        .iter()
+        .find(|def| def.name.eq_ignore_ascii_case(name))
+}
+
+/// makes the item a key refers to, if it is in the catalog.
+pub fn make(key: &ItemKey) -> Option<Box<dyn Item>> {
+    lookup(&key.name).map(|def| {
+        Box::new(CatalogItem {
+            def,
+            inst: key.inst,
+        }) as Box<dyn Item>
+    })
+}
+
+/// the key a biomes boss guards.
+pub fn biome_key(biome: Biome) -> ItemKey {
+    ItemKey::new(match biome {
+        Biome::Forest => "Forest Key",
+        Biome::Temple => "Temple Key",
+        Biome::Desert => "Desert Key",
+        Biome::Mountains => "Mountain Key",
+        Biome::Ruins => "Ruins Key",
+        Biome::Dungeon => "Dungeon Key",
+        Biome::Town => "Town Key",
+    })
+}
+
+/// an item from the catalog.
+pub struct CatalogItem {
+    def: &'static ItemDef,
+    inst: u8,
+}
+
+impl Item for CatalogItem {
+    fn key(&self) -> ItemKey {
+        ItemKey {
+            name: Arc::from(self.def.name),
+            inst: self.inst,
+        }
+    }
+
+    fn name(&self) -> Arc<str> {
+        Arc::from(self.def.name)
+    }
+
+    fn desc(&self) -> Arc<str> {
+        Arc::from(self.def.desc)
+    }
+
+    fn category(&self) -> Category {
+        self.def.category
+    }
+
+    fn value(&self) -> u16 {
+        self.def.value
+    }
+
+    fn target_mode(&self) -> TargetMode {
+        match self.def.does {
+            Use::Heal(_) | Use::Status(..) => TargetMode::Ally,
+            Use::Spell(name) => {
+                spells::learn(name).map_or(TargetMode::TheSelf, |spell| spell.target_mode())
+            }
+            Use::Bomb(_) => TargetMode::All,
+            Use::Escape | Use::Gear(..) | Use::Recipe | Use::Nothing => TargetMode::TheSelf,
+        }
+    }
+
+    fn use_on(&mut self, user: &Stats, target: &Stats) -> Option<Effect> {
+        match self.def.does {
+            Use::Heal(amount) => Some(Effect::Heal(amount)),
+            Use::Status(kind, turns, potency) => {
+                Some(Effect::Status(Box::new(Status::new(kind, turns, potency))))
+            }
+            Use::Spell(name) => spells::learn(name).map(|mut spell| spell.cast(user, target)),
+            Use::Bomb(power) => Some(Effect::Damage(Damage {
+                amount: damage::mitigate(power, target.mg_def),
+                kind: DamageType::Magical,
+                crit: false,
+            })),
+            Use::Escape => Some(Effect::Escape),
+            Use::Gear(..) | Use::Recipe | Use::Nothing => None,
+        }
+    }
+
+    fn equipment(&self) -> Option<Equipment> {
+        let Use::Gear(kind, [hp, str, mg_str, def, mg_def, speed]) = self.def.does else {
+            return None;
+        };
+        let stat = |n: i8| BuffType::Const(n as i16);
+
+        Some(Equipment {
+            key: self.key(),
+            kind,
+            buff: Buff {
+                hp: stat(hp),
+                str: stat(str),
+                mg_str: stat(mg_str),
+                def: stat(def),
+                mg_def: stat(mg_def),
+                speed: stat(speed),
+            },
+        })
+    }
+}
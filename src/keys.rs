@@ -0,0 +1,207 @@
+use crate::fame::Memorial;
+use crate::game::GameState;
+use crate::items;
+use crate::level::{Biome, Level};
+use crate::lore;
+use crate::world::World;
+use crate::Player;
+use anyhow::{bail, Result};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// mixed into every proof so a key can't be made by hashing the same things in a different way.
+const PROOF_SALT: &[u8] = b"rogue-xork biome key";
+
+/// 64 bit FNV-1a. small and simple enough to run anywhere, which is all a proof needs here.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+/// a secret only the `biome` level built from `seed` knows. every node draws the same one from
+/// the seed, so a key traded from another device (or kept over a reboot) can still be checked.
+fn secret(biome: Biome, seed: u64) -> u64 {
+    StdRng::seed_from_u64(seed ^ fnv1a(biome.to_string().as_bytes())).gen()
+}
+
+/// the proof that `holder` beat the boss of the `biome` level built from `seed`.
+fn proof(biome: Biome, seed: u64, holder: &str) -> u64 {
+    let mut bytes = PROOF_SALT.to_vec();
+    bytes.extend(secret(biome, seed).to_le_bytes());
+    bytes.extend(biome.to_string().bytes());
+    bytes.extend(seed.to_le_bytes());
+    bytes.extend(holder.bytes());
+
+    fnv1a(&bytes)
+}
+
+/// the key a biomes boss guards. it remembers which level it came from and who earned it, so a
+/// key won in a traded world can be checked from the key alone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BiomeKey {
+    pub biome: Biome,
+    /// the seed of the level the key was won in.
+    pub seed: u64,
+    proof: u64,
+}
+
+impl BiomeKey {
+    /// the key for beating the boss of a level. there is no key until the boss is slain.
+    pub fn earn(level: &Level, holder: &str) -> Result<Self> {
+        if !level.boss_room.slain {
+            bail!("the {} is still guarded.", name(level.biome));
+        }
+
+        Ok(Self {
+            biome: level.biome,
+            seed: level.seed,
+            proof: proof(level.biome, level.seed, holder),
+        })
+    }
+
+    /// true if `holder` really did earn this key, in the level of its own biome and seed.
+    pub fn genuine(&self, holder: &str) -> bool {
+        self.proof == proof(self.biome, self.seed, holder)
+    }
+}
+
+/// what a biomes key is called.
+pub fn name(biome: Biome) -> Arc<str> {
+    items::biome_key(biome).name
+}
+
+impl fmt::Display for BiomeKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", name(self.biome))
+    }
+}
+
+/// the biome keys the player has won, one for each biome. holding all of them opens the way to
+/// the end of the game.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRing {
+    /// the key for each biome, in the order of Biome::ALL.
+    keys: [Option<BiomeKey>; 7],
+    /// true once the keys have been used to open the gate, and the game is won.
+    pub opened: bool,
+}
+
+impl KeyRing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the key for a biome, if the player has it.
+    pub fn get(&self, biome: Biome) -> Option<&BiomeKey> {
+        self.keys[biome as usize].as_ref()
+    }
+
+    /// how many keys are on the ring.
+    pub fn count(&self) -> usize {
+        self.keys.iter().flatten().count()
+    }
+
+    /// true once there is a key for every biome.
+    pub fn complete(&self) -> bool {
+        self.count() == Biome::ALL.len()
+    }
+
+    /// puts a key on the ring. keys that weren't earned by `holder` are turned away. returns
+    /// false if there was already a key for that biome.
+    pub fn add(&mut self, key: BiomeKey, holder: &str) -> Result<bool> {
+        if !key.genuine(holder) {
+            bail!("the {key} crumbles to dust in your hand. it was never yours.");
+        }
+
+        let slot = &mut self.keys[key.biome as usize];
+
+        if slot.is_some() {
+            return Ok(false);
+        }
+
+        *slot = Some(key);
+        Ok(true)
+    }
+
+    /// the biomes whose keys weren't earned by `holder`. (eg, a ring copied from someone else)
+    pub fn forged(&self, holder: &str) -> Vec<Biome> {
+        self.keys
+            .iter()
+            .flatten()
+            .filter(|key| !key.genuine(holder))
+            .map(|key| key.biome)
+            .collect()
+    }
+
+    /// uses the keys to open the gate. every key must be there, and all of them genuine.
+    pub fn open(&mut self, holder: &str) -> Result<()> {
+        if !self.complete() {
+            bail!(
+                "the gate has seven locks. you hold {} of their keys.",
+                self.count()
+            );
+        }
+
+        if let Some(biome) = self.forged(holder).first() {
+            bail!("the {} won't turn. it was never yours.", name(*biome));
+        }
+
+        self.opened = true;
+        Ok(())
+    }
+
+    /// the key ring as the player sees it.
+    pub fn listing(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "your key ring ({}/{}):",
+            self.count(),
+            Biome::ALL.len()
+        )];
+
+        for biome in Biome::ALL {
+            match self.get(biome) {
+                Some(key) => lines.push(format!(
+                    "  {key} - {}",
+                    items::lookup(&name(biome)).map_or("", |def| def.desc)
+                )),
+                None => lines.push(format!("  ({biome} - missing)")),
+            }
+        }
+
+        lines
+    }
+}
+
+/// the end of the game. the gate opens and the story plays out one line at a time, then the
+/// players victory goes into the hall of fame.
+#[derive(Debug, Default)]
+pub struct GateState {
+    /// the next line of lore::ENDING to show.
+    line: usize,
+}
+
+impl GameState for GateState {
+    #[allow(unused_variables)]
+    fn step(&mut self, cmd: &str, player: &mut Player, world: &mut World) -> bool {
+        if let Some(line) = lore::ENDING.get(self.line) {
+            println!("{}", line.replace("{name}", &player.name));
+            self.line += 1;
+        }
+
+        if self.is_done() && world.fame.record(Memorial::new(player)) {
+            println!("your victory is recorded in the hall of fame. (fame)");
+        }
+
+        self.is_done()
+    }
+
+    fn is_done(&self) -> bool {
+        self.line >= lore::ENDING.len()
+    }
+}
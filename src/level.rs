@@ -1,5 +1,9 @@
-use crate::enemies::Enemy;
-use crate::zone::{BossRoom, Zone};
+use crate::allies;
+use crate::enemies::{Enemy, MobSpawner};
+use crate::loot::Stash;
+use crate::lore::ZoneNamer;
+use crate::shop::Shop;
+use crate::zone::{BossRoom, Zone, ZoneType};
 use anyhow::Result;
 use kahuna::bitset_state::BitsetState;
 use kahuna::square_grid::SquareGrid;
@@ -7,12 +11,14 @@ use kahuna::{collapse, set_rule::*, AllState};
 use log::info;
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
 use std::fmt;
 use std::fmt::Formatter;
 use std::ops::{Index, IndexMut};
 use strum_macros::EnumString;
 
-type Coords = (usize, usize);
+pub type Coords = (usize, usize);
 type S = BitsetState<9>;
 
 const SHELTER: S = S::state(0);
@@ -25,13 +31,18 @@ const TRESURE_ROOM: S = S::state(6);
 const BOSS_ENTRY: S = S::state(7);
 const WALL: S = S::state(8);
 
-const NORTH: (isize, isize) = (0, -1);
-const SOUTH: (isize, isize) = (0, 1);
-const WEST: (isize, isize) = (1, 0);
-const EAST: (isize, isize) = (-1, 0);
+pub const NORTH: (isize, isize) = (0, -1);
+pub const SOUTH: (isize, isize) = (0, 1);
+pub const WEST: (isize, isize) = (1, 0);
+pub const EAST: (isize, isize) = (-1, 0);
 
 type Grid = SquareGrid<S>;
 
+/// mixes a level's seed with a zone's coordinates so each zone can have its own stable rng.
+pub fn zone_seed(seed: u64, at: Coords) -> u64 {
+    seed ^ ((at.0 as u64) << 32 | at.1 as u64)
+}
+
 fn get_rules() -> SetCollapseRule<S, Grid, UniformSetCollapseObserver> {
     SetCollapseRuleBuilder::new(UniformSetCollapseObserver)
         .allow(
@@ -197,8 +208,8 @@ fn get_rules() -> SetCollapseRule<S, Grid, UniformSetCollapseObserver> {
                 (WEST, SHELTER | COREDOR | TEMPLE),
             ],
         )
-        // TODO: might need to remove this. bc this may cause there to be more then one boss
-        // entry. and thats bad.
+        // NOTE: this may cause there to be more then one boss entry. mk_level only keeps the one at
+        // boss_portal and turns the rest into open paths.
         .allow(
             &BOSS_ENTRY,
             &[
@@ -264,7 +275,7 @@ fn get_rules() -> SetCollapseRule<S, Grid, UniformSetCollapseObserver> {
         .build()
 }
 
-#[derive(Debug, Serialize, Deserialize, Copy, Clone, EnumString)]
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, EnumString)]
 #[strum(ascii_case_insensitive)]
 pub enum Biome {
     Forest,
@@ -276,6 +287,19 @@ pub enum Biome {
     Town,
 }
 
+impl Biome {
+    /// every biome, in order.
+    pub const ALL: [Biome; 7] = [
+        Biome::Forest,
+        Biome::Temple,
+        Biome::Desert,
+        Biome::Mountains,
+        Biome::Ruins,
+        Biome::Dungeon,
+        Biome::Town,
+    ];
+}
+
 impl fmt::Display for Biome {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -311,11 +335,15 @@ pub struct LevelBuilder {
     warp_in: (isize, isize),
     /// the location of the portal to the boss's lair.
     boss_portal: (isize, isize),
+    /// seeds everything about the level that needs to be the same every time it's built (zone
+    /// names, etc).
+    seed: u64,
 }
 
 impl LevelBuilder {
     pub fn new() -> Self {
         let mut rng = rand::thread_rng();
+        let seed = rng.gen();
         let dim: (isize, isize) = (rng.gen_range(20..30), rng.gen_range(20..30));
         let warp_in = (rng.gen_range(0..dim.0), rng.gen_range(0..dim.1));
         let mut boss_portal = (rng.gen_range(0..dim.0), rng.gen_range(0..dim.1));
@@ -328,6 +356,7 @@ impl LevelBuilder {
             dim,
             warp_in,
             boss_portal,
+            seed,
         }
     }
 
@@ -368,31 +397,44 @@ impl LevelBuilder {
     }
 
     fn mk_level(&self, template: Grid) -> Level {
-        let mut zones: Vec<Box<[Zone]>> = Vec::with_capacity(self.dim.0 as usize);
-        let blank: Vec<Zone> = Vec::with_capacity(self.dim.1 as usize);
+        let mut zones: Vec<Box<[Zone]>> = Vec::with_capacity(self.dim.1 as usize);
+        let blank: Vec<Zone> = Vec::with_capacity(self.dim.0 as usize);
+        let biome = biome!();
+        let mut namer = ZoneNamer::new(self.seed, biome);
 
-        for y in 0..self.dim.0 {
+        for y in 0..self.dim.1 {
             let mut row = blank.clone();
 
-            for x in 0..self.dim.1 {
+            for x in 0..self.dim.0 {
                 // info!("{:?}", template[(x, y)]);
 
-                let zone = match template[(x, y)] {
-                    // TODO: make this create zones based on the state
-                    SHELTER => Zone::new(&String::new()),
-                    OPEN_PATH => Zone::new(&String::new()),
-                    COREDOR => Zone::new(&String::new()),
-                    TEMPLE => Zone::new(&String::new()),
-                    MOUNTAINS => Zone::new(&String::new()),
-                    SANCTUARY => Zone::new(&String::new()),
-                    TRESURE_ROOM => Zone::new(&String::new()),
-                    BOSS_ENTRY => Zone::new(&String::new()),
-                    WALL => Zone::new(&String::new()),
+                let kind = match template[(x, y)] {
+                    _ if (x, y) == self.warp_in => ZoneType::Sanctuary,
+                    _ if (x, y) == self.boss_portal => ZoneType::BossEntry,
+                    SHELTER => ZoneType::Shelter,
+                    OPEN_PATH => ZoneType::OpenPath,
+                    COREDOR => ZoneType::Coredor,
+                    TEMPLE => ZoneType::Temple,
+                    MOUNTAINS => ZoneType::Mountains,
+                    SANCTUARY => ZoneType::Sanctuary,
+                    TRESURE_ROOM => ZoneType::TreasureRoom,
+                    // there is only one way into the boss room, any other portals the collapse
+                    // produced are just paths.
+                    BOSS_ENTRY => ZoneType::OpenPath,
+                    WALL => ZoneType::Wall,
                     _ => {
                         unreachable!("all zone types should be enumerated in this match statement.")
                     }
                 };
 
+                let mut zone = Zone::new(&String::new());
+                let seed = zone_seed(self.seed, (x as usize, y as usize));
+                zone.enemies = MobSpawner::new(biome, &kind, seed);
+                zone.companion = allies::hireling(biome, &kind, seed);
+                zone.loot = Stash::new(biome, &kind, seed);
+                zone.shop = Shop::new(biome, &kind, seed);
+                zone.feild_type(kind);
+                namer.dress(&mut zone);
                 row.push(zone);
             }
 
@@ -405,17 +447,20 @@ impl LevelBuilder {
             warp_in: self.warp_in,
             boss_portal: self.boss_portal,
             boss_room: BossRoom::new(None),
-            biome: biome!(),
+            biome,
+            seed: self.seed,
         }
     }
 
     /// returns true if the player can make it from the warp in to the boss battle. else returns
     /// false.
     fn traversable(&self, level: &Level) -> bool {
-        // TODO: preforms A* on level from the warp_in sanctuary to the boss_portal.
-        // this ensures that the player can at least get to the boss room level is at
-        // least playable.
-        true
+        // preforms A* on level from the warp_in sanctuary to the boss_portal. this ensures that
+        // the player can at least get to the boss room level is at least playable.
+        let start = (self.warp_in.0 as usize, self.warp_in.1 as usize);
+        let goal = (self.boss_portal.0 as usize, self.boss_portal.1 as usize);
+
+        level.find_path(start, goal).is_some()
     }
 
     /// chooses zones based on the biome and surrounding zones
@@ -451,9 +496,94 @@ pub struct Level {
     pub boss_room: BossRoom,
     /// defines the biome type of the level
     pub biome: Biome,
+    /// the seed the level was built from.
+    pub seed: u64,
 }
 
 impl Level {
+    /// returns true if the coordinates are inside the level and the zone there can be walked
+    /// through.
+    pub fn walkable(&self, loc: Coords) -> bool {
+        (loc.0 as isize) < self.dim.0
+            && (loc.1 as isize) < self.dim.1
+            && self[loc].feild_type != ZoneType::Wall
+    }
+
+    /// how many zones in the level can be walked through.
+    pub fn explorable(&self) -> usize {
+        self.zones
+            .iter()
+            .flat_map(|row| row.iter())
+            .filter(|zone| zone.feild_type != ZoneType::Wall)
+            .count()
+    }
+
+    /// returns the coordinates one step from `loc` in direction `dir` (one of NORTH, SOUTH, EAST,
+    /// or WEST), if that step lands on a walkable zone.
+    pub fn step(&self, loc: Coords, dir: (isize, isize)) -> Option<Coords> {
+        let x = loc.0 as isize + dir.0;
+        let y = loc.1 as isize + dir.1;
+
+        if x < 0 || y < 0 || !self.walkable((x as usize, y as usize)) {
+            None
+        } else {
+            Some((x as usize, y as usize))
+        }
+    }
+
+    /// finds the shortest walkable route between two zones using A*. the returned path starts
+    /// with the first step after `from` and ends with `to`, so its length is the number of moves
+    /// needed. returns None if `to` can not be reached.
+    pub fn find_path(&self, from: Coords, to: Coords) -> Option<Vec<Coords>> {
+        if !self.walkable(from) || !self.walkable(to) {
+            return None;
+        }
+
+        let dist = |a: Coords| a.0.abs_diff(to.0) + a.1.abs_diff(to.1);
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<Coords, Coords> = HashMap::new();
+        let mut cost: HashMap<Coords, usize> = HashMap::new();
+
+        cost.insert(from, 0);
+        open.push(Reverse((dist(from), from)));
+
+        while let Some(Reverse((_, here))) = open.pop() {
+            if here == to {
+                let mut path = vec![here];
+                let mut here = here;
+
+                while let Some(prev) = came_from.get(&here) {
+                    if *prev == from {
+                        break;
+                    }
+
+                    path.push(*prev);
+                    here = *prev;
+                }
+
+                path.reverse();
+
+                return Some(if from == to { Vec::new() } else { path });
+            }
+
+            let next_cost = cost[&here] + 1;
+
+            for dir in [NORTH, SOUTH, EAST, WEST] {
+                let Some(next) = self.step(here, dir) else {
+                    continue;
+                };
+
+                if cost.get(&next).is_none_or(|c| next_cost < *c) {
+                    cost.insert(next, next_cost);
+                    came_from.insert(next, here);
+                    open.push(Reverse((next_cost + dist(next), next)));
+                }
+            }
+        }
+
+        None
+    }
+
     // pub fn new(boss: Box<dyn Enemy>) -> Self {
     //     let mut rng = rand::thread_rng();
     //     let dim: (isize, isize) = (rng.gen_range(10..25), rng.gen_range(10..25));
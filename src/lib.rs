@@ -1,15 +1,33 @@
 use anyhow::bail;
 use enemies::Enemy;
-use esp_idf_hal::{delay::BLOCK, i2c::I2cDriver};
-use items::Item;
+use esp_idf_hal::{
+    delay::BLOCK,
+    i2c::{I2cDriver, I2cSlaveDriver},
+};
+use fame::HallOfFame;
+use level::Biome;
 use player::Player;
+use std::sync::Mutex;
 
+pub mod allies;
 pub mod battle_logic;
+pub mod crafting;
+pub mod damage;
 pub mod enemies;
+pub mod experience;
+pub mod fame;
 pub mod game;
+pub mod inventory;
 pub mod items;
+pub mod keys;
 pub mod level;
+pub mod loot;
+pub mod lore;
 pub mod player;
+pub mod score;
+pub mod shop;
+pub mod spells;
+pub mod status;
 pub mod world;
 pub mod zone;
 
@@ -20,6 +38,13 @@ pub const LEVEL_MAX: u8 = 15;
 pub const ZONE_DIM_MIN: u8 = 7;
 pub const ZONE_DIM_MAX: u8 = 10;
 pub const PACKET_SIZE: usize = u8::MAX as usize;
+/// the request code for pinging a node to see if it's there. (see API-specs.md)
+pub const PING: u8 = 3;
+/// the request code for swapping halls of fame with another node. (see API-specs.md)
+pub const SWAP_FAME: u8 = 4;
+/// the first i2c address a node answers requests on. every biome runs on its own board, so each
+/// node answers on WORKER_ADDRESS plus its biome.
+pub const WORKER_ADDRESS: I2cAdr = 0x10;
 
 pub trait I2cCom<T> {
     // : erased_serde::Serialize {
@@ -50,7 +75,8 @@ fn _msg_len(mesg: &[u8]) -> anyhow::Result<u16> {
     }
 }
 
-fn i2c_write(i2c: &mut I2cDriver, addr: I2cAdr, mesage: &[u8]) -> anyhow::Result<()> {
+/// pads a message with null bytes out to a whole number of packets.
+fn pad(mesage: &[u8]) -> Vec<u8> {
     let mut mesg = Vec::from(mesage);
     let mut n_packets = mesg.len() / PACKET_SIZE;
 
@@ -61,7 +87,14 @@ fn i2c_write(i2c: &mut I2cDriver, addr: I2cAdr, mesage: &[u8]) -> anyhow::Result
         }
     }
 
-    for packet_i in 0..n_packets - 1 {
+    mesg
+}
+
+fn i2c_write(i2c: &mut I2cDriver, addr: I2cAdr, mesage: &[u8]) -> anyhow::Result<()> {
+    let mesg = pad(mesage);
+    let n_packets = mesg.len() / PACKET_SIZE;
+
+    for packet_i in 0..n_packets {
         i2c.write(
             addr,
             &mesg[(PACKET_SIZE * packet_i)..(PACKET_SIZE * (packet_i + 1))],
@@ -110,3 +143,81 @@ fn send_cmd_read(
         i2c_read(i2c, addr)
     }
 }
+
+/// sends our hall of fame to the node at `addr`, and merges in the one it sends back.
+pub fn swap_fame(i2c: &mut I2cDriver, addr: I2cAdr, fame: &mut HallOfFame) -> anyhow::Result<()> {
+    let reply = send_cmd_read(i2c, addr, SWAP_FAME, &fame.encode()?)?;
+
+    if !reply.is_empty() {
+        let theirs = fame.decode_from(&reply)?;
+        fame.merge(theirs);
+    }
+
+    Ok(())
+}
+
+/// the address a node running `biome` answers requests on.
+pub fn worker_address(biome: Biome) -> I2cAdr {
+    WORKER_ADDRESS + biome as u8
+}
+
+/// reads the argument of a request, on the worker side. packets are always sent whole, so this
+/// keeps reading until the packet holding the end of the message is all in.
+fn worker_read(slave: &mut I2cSlaveDriver) -> anyhow::Result<Vec<u8>> {
+    let mut res = Vec::new();
+
+    while res.len() % PACKET_SIZE != 0 || !res.contains(&b'\n') {
+        let mut buf: [u8; PACKET_SIZE] = [0; PACKET_SIZE];
+        let want = PACKET_SIZE - res.len() % PACKET_SIZE;
+        let n = slave.read(&mut buf[..want], BLOCK)?;
+
+        res.extend_from_slice(&buf[..n]);
+    }
+
+    Ok(res)
+}
+
+/// sends a response code and its argument (if it has one) back to the controller, on the worker
+/// side. see send_cmd_read for the other end.
+fn worker_reply(slave: &mut I2cSlaveDriver, mesg: &[u8]) -> anyhow::Result<()> {
+    let (code, mesg) = match mesg.is_empty() {
+        true => (1, Vec::new()),
+        false => (0, pad(mesg)),
+    };
+    let mut data = vec![code];
+    data.extend(mesg);
+
+    // the transmit buffer may not take it all at once.
+    let mut sent = 0;
+
+    while sent < data.len() {
+        sent += slave.write(&data[sent..], BLOCK)?;
+    }
+
+    Ok(())
+}
+
+/// answers one request from another node, on the worker side. halls of fame are merged into
+/// `fame` and it's sent back. requests this node doesn't handle are answered with no data
+/// straight away, their arguments (if any) are never read since their size isn't known here.
+pub fn serve(slave: &mut I2cSlaveDriver, fame: &Mutex<HallOfFame>) -> anyhow::Result<()> {
+    let mut code: [u8; 1] = [0];
+    slave.read(&mut code, BLOCK)?;
+
+    match code[0] {
+        // pings have no argument and get no reply, being able to write the code is enough.
+        PING => Ok(()),
+        SWAP_FAME => {
+            let mesg = worker_read(slave)?;
+            let Ok(mut fame) = fame.lock() else {
+                bail!("the hall of fame was poisoned by a panic");
+            };
+
+            let theirs = fame.decode_from(&mesg)?;
+            fame.merge(theirs);
+
+            worker_reply(slave, &fame.encode()?)
+        }
+        _ => worker_reply(slave, &[]),
+    }
+}
@@ -0,0 +1,418 @@
+use crate::items::ItemKey;
+use crate::level::Biome;
+use crate::player::Stats;
+use crate::zone::ZoneType;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// the key that opens locked chests. it's used up when a chest is opened.
+pub const CHEST_KEY: &str = "Rusty Key";
+
+/// how rare an item is. rarer items are worth more and turn up less.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
+}
+
+impl fmt::Display for Rarity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rarity::Common => write!(f, "common"),
+            Rarity::Uncommon => write!(f, "uncommon"),
+            Rarity::Rare => write!(f, "rare"),
+            Rarity::Legendary => write!(f, "legendary"),
+        }
+    }
+}
+
+const TIERS: [Rarity; 4] = [
+    Rarity::Common,
+    Rarity::Uncommon,
+    Rarity::Rare,
+    Rarity::Legendary,
+];
+
+/// one item a loot table can drop.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LootEntry {
+    /// the name of the item in the item catalog.
+    pub item: &'static str,
+    pub rarity: Rarity,
+}
+
+const fn entry(item: &'static str, rarity: Rarity) -> LootEntry {
+    LootEntry { item, rarity }
+}
+
+use Rarity::*;
+
+// entry(item, rarity)
+const COMMON: [LootEntry; 19] = [
+    entry("Potion", Common),
+    entry("Healing Herb", Common),
+    entry("Iron Ore", Common),
+    entry("Leather Cap", Common),
+    entry("Leather Leggings", Common),
+    entry("Rusty Sword", Common),
+    entry("Rusty Key", Uncommon),
+    entry("Greater Potion", Uncommon),
+    entry("Firebomb", Uncommon),
+    entry("Smoke Bomb", Uncommon),
+    entry("Scroll of Healing", Uncommon),
+    entry("Crafting Notes", Uncommon),
+    entry("Regen Draught", Uncommon),
+    entry("Leather Armor", Uncommon),
+    entry("Buckler", Uncommon),
+    entry("Iron Sword", Rare),
+    entry("Chainmail", Rare),
+    entry("Ring of Swiftness", Legendary),
+    entry("Mage Robe", Legendary),
+];
+const FOREST: [LootEntry; 3] = [
+    entry("Wolf Pelt", Common),
+    entry("Haste Tonic", Uncommon),
+    entry("Oak Staff", Rare),
+];
+const TEMPLE: [LootEntry; 3] = [
+    entry("Scroll of Fireball", Uncommon),
+    entry("Spell Tome", Rare),
+    entry("Amulet of Warding", Rare),
+];
+const DESERT: [LootEntry; 3] = [
+    entry("Firebomb", Common),
+    entry("Scroll of Fireball", Rare),
+    entry("War Axe", Rare),
+];
+const MOUNTAINS: [LootEntry; 3] = [
+    entry("Stoneskin Tonic", Uncommon),
+    entry("Iron Helm", Uncommon),
+    entry("Gauntlets", Uncommon),
+];
+const RUINS: [LootEntry; 3] = [
+    entry("Scroll of Calling", Uncommon),
+    entry("Spell Tome", Uncommon),
+    entry("Amulet of Warding", Rare),
+];
+const DUNGEON: [LootEntry; 3] = [
+    entry("Rusty Key", Common),
+    entry("Gauntlets", Uncommon),
+    entry("War Axe", Rare),
+];
+const TOWN: [LootEntry; 3] = [
+    entry("Potion", Common),
+    entry("Haste Tonic", Uncommon),
+    entry("Ring of Swiftness", Rare),
+];
+
+/// the items that can only be found in a biome. every biome can drop the COMMON table too.
+pub fn loot_table(biome: Biome) -> &'static [LootEntry] {
+    match biome {
+        Biome::Forest => &FOREST,
+        Biome::Temple => &TEMPLE,
+        Biome::Desert => &DESERT,
+        Biome::Mountains => &MOUNTAINS,
+        Biome::Ruins => &RUINS,
+        Biome::Dungeon => &DUNGEON,
+        Biome::Town => &TOWN,
+    }
+}
+
+/// how much loot a kind of zone holds.
+struct ZoneLoot {
+    /// the fewest and most items in plain sight.
+    ground: (u8, u8),
+    /// the fewest and most items hidden away.
+    hidden: (u8, u8),
+    /// the chance (out of 100) of a chest.
+    chest: u8,
+    /// how likely each tier is. (common, uncommon, rare, legendary)
+    tiers: [u8; 4],
+}
+
+const PLAIN: [u8; 4] = [60, 28, 10, 2];
+const RICH: [u8; 4] = [20, 40, 30, 10];
+
+fn zone_loot(kind: &ZoneType) -> Option<ZoneLoot> {
+    let loot = |ground, hidden, chest, tiers| ZoneLoot {
+        ground,
+        hidden,
+        chest,
+        tiers,
+    };
+
+    match kind {
+        ZoneType::Shelter => Some(loot((0, 0), (0, 1), 0, PLAIN)),
+        ZoneType::OpenPath => Some(loot((0, 0), (0, 1), 5, PLAIN)),
+        ZoneType::Coredor => Some(loot((0, 0), (0, 2), 10, PLAIN)),
+        ZoneType::Temple => Some(loot((0, 0), (1, 2), 15, RICH)),
+        ZoneType::Mountains => Some(loot((0, 0), (0, 2), 10, PLAIN)),
+        // treasure rooms always have something to show for the trip.
+        ZoneType::TreasureRoom => Some(loot((2, 3), (1, 2), 100, RICH)),
+        ZoneType::Sanctuary | ZoneType::BossEntry | ZoneType::Wall => None,
+    }
+}
+
+/// how rare an item is, by name. items that aren't in any loot table are only ever found one way
+/// (boss drops and the like) and count as Legendary.
+pub fn rarity(name: &str) -> Rarity {
+    COMMON
+        .iter()
+        .chain(Biome::ALL.into_iter().flat_map(loot_table))
+        .filter(|entry| entry.item.eq_ignore_ascii_case(name))
+        .map(|entry| entry.rarity)
+        .min()
+        .unwrap_or(Legendary)
+}
+
+/// rolls one item from a biomes loot.
+fn roll_item(biome: Biome, tiers: &[u8; 4], rng: &mut StdRng) -> ItemKey {
+    roll_item_from(loot_table(biome), tiers, rng)
+}
+
+/// rolls one item from the COMMON table and a biome `table`. `tiers` is how likely each tier is.
+/// (common, uncommon, rare, legendary)
+pub fn roll_item_from(table: &[LootEntry], tiers: &[u8; 4], rng: &mut StdRng) -> ItemKey {
+    let tier = (0..TIERS.len())
+        .collect::<Vec<_>>()
+        .choose_weighted(rng, |i| tiers[*i])
+        .map_or(Rarity::Common, |i| TIERS[*i]);
+
+    // fall back to rarer and then commoner tiers if the biome has nothing at this one.
+    let pool: Vec<&LootEntry> = COMMON.iter().chain(table).collect();
+    let entry = TIERS
+        .iter()
+        .filter(|rarity| **rarity >= tier)
+        .chain(TIERS.iter().rev())
+        .find_map(|rarity| {
+            let tier: Vec<_> = pool.iter().filter(|e| e.rarity == *rarity).collect();
+            tier.choose(rng).map(|e| e.item)
+        })
+        .unwrap_or(COMMON[0].item);
+
+    ItemKey::new(entry)
+}
+
+/// a chest. locked chests need a CHEST_KEY to open.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chest {
+    pub locked: bool,
+    pub items: Vec<ItemKey>,
+}
+
+/// the loot in a zone. items are taken out as the player picks them up, so once something is
+/// gone it stays gone.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stash {
+    /// items lying in plain sight.
+    pub ground: Vec<ItemKey>,
+    /// items that turn up when the zone is searched.
+    pub hidden: Vec<ItemKey>,
+    /// true once the zone has been searched.
+    pub searched: bool,
+    pub chest: Option<Chest>,
+}
+
+impl Stash {
+    /// rolls the loot of a zone, or None if nothing is ever found in that kind of zone.
+    pub fn new(biome: Biome, kind: &ZoneType, seed: u64) -> Option<Self> {
+        let loot = zone_loot(kind)?;
+        let mut rng = StdRng::seed_from_u64(seed.rotate_left(29));
+        let roll = |(min, max): (u8, u8), rng: &mut StdRng| -> Vec<ItemKey> {
+            (0..rng.gen_range(min..=max))
+                .map(|_| roll_item(biome, &loot.tiers, rng))
+                .collect()
+        };
+
+        let ground = roll(loot.ground, &mut rng);
+        let hidden = roll(loot.hidden, &mut rng);
+        let chest = (rng.gen_range(0..100) < loot.chest).then(|| Chest {
+            locked: rng.gen_bool(0.5),
+            items: roll((1, 3), &mut rng),
+        });
+
+        Some(Self {
+            ground,
+            hidden,
+            searched: false,
+            chest,
+        })
+    }
+
+    /// searches the zone. returns whatever turns up, it ends up on the ground.
+    pub fn search(&mut self) -> Vec<ItemKey> {
+        self.searched = true;
+        let found = std::mem::take(&mut self.hidden);
+        self.ground.extend(found.iter().cloned());
+
+        found
+    }
+
+    /// picks up an item lying on the ground by name, ignoring case.
+    pub fn take(&mut self, name: &str) -> Option<ItemKey> {
+        let i = self
+            .ground
+            .iter()
+            .position(|key| key.name.eq_ignore_ascii_case(name))?;
+
+        Some(self.ground.remove(i))
+    }
+
+    /// opens the chest, spilling what's inside onto the ground. `has_key` is asked whether the
+    /// player can open a locked chest (and should use up the key if they can). returns what was
+    /// inside, or None if there is no chest or it stays locked.
+    pub fn open(&mut self, has_key: impl FnOnce() -> bool) -> Option<Vec<ItemKey>> {
+        let chest = self.chest.as_ref()?;
+
+        if chest.locked && !has_key() {
+            return None;
+        }
+
+        let chest = self.chest.take()?;
+        self.ground.extend(chest.items.iter().cloned());
+
+        Some(chest.items)
+    }
+}
+
+/// what an enemy can drop when it's defeated.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DropTable {
+    /// the name of the enemy. (without the "#n" numbering of enemies in a group)
+    pub enemy: &'static str,
+    /// the least and most gold dropped.
+    pub gold: (u16, u16),
+    /// items that may drop, as (name, chance out of 100). each one is rolled separately.
+    pub items: &'static [(&'static str, u8)],
+}
+
+const fn drops(
+    enemy: &'static str,
+    gold: (u16, u16),
+    items: &'static [(&'static str, u8)],
+) -> DropTable {
+    DropTable { enemy, gold, items }
+}
+
+// drops(enemy, gold, [(item, chance)])
+const DROPS: [DropTable; 21] = [
+    drops("Wolf", (0, 2), &[("Wolf Pelt", 40), ("Healing Herb", 10)]),
+    drops(
+        "Goblin",
+        (3, 8),
+        &[("Potion", 10), ("Rusty Sword", 8), ("Rusty Key", 5)],
+    ),
+    drops(
+        "Dryad",
+        (1, 4),
+        &[("Healing Herb", 50), ("Dryad Sap", 40), ("Oak Staff", 3)],
+    ),
+    drops(
+        "Acolyte",
+        (4, 9),
+        &[("Potion", 10), ("Holy Ash", 30), ("Scroll of Healing", 10)],
+    ),
+    drops(
+        "Stone Guardian",
+        (2, 6),
+        &[("Iron Ore", 50), ("Iron Helm", 5)],
+    ),
+    drops(
+        "Wraith",
+        (3, 8),
+        &[
+            ("Ectoplasm", 35),
+            ("Regen Draught", 8),
+            ("Amulet of Warding", 3),
+        ],
+    ),
+    drops(
+        "Scorpion",
+        (0, 3),
+        &[("Scorpion Stinger", 40), ("Healing Herb", 10)],
+    ),
+    drops(
+        "Bandit",
+        (6, 14),
+        &[
+            ("Potion", 15),
+            ("Firebomb", 8),
+            ("Smoke Bomb", 6),
+            ("Rusty Key", 8),
+            ("Iron Sword", 3),
+        ],
+    ),
+    drops(
+        "Sand Wraith",
+        (3, 8),
+        &[("Ectoplasm", 20), ("Scroll of Fireball", 6)],
+    ),
+    drops("Harpy", (1, 5), &[("Harpy Feather", 45), ("Potion", 8)]),
+    drops(
+        "Ice Imp",
+        (2, 6),
+        &[("Frost Shard", 35), ("Stoneskin Tonic", 10)],
+    ),
+    drops("Rock Troll", (4, 10), &[("Iron Ore", 60), ("War Axe", 3)]),
+    drops(
+        "Skeleton",
+        (2, 6),
+        &[("Bone Dust", 45), ("Rusty Sword", 10), ("Leather Cap", 8)],
+    ),
+    drops("Ghoul", (2, 6), &[("Bone Dust", 20), ("Healing Herb", 10)]),
+    drops(
+        "Cultist",
+        (5, 10),
+        &[
+            ("Crafting Notes", 8),
+            ("Scroll of Calling", 6),
+            ("Spell Tome", 3),
+        ],
+    ),
+    drops("Rat Swarm", (0, 2), &[("Cloth Scraps", 15)]),
+    drops("Slime", (1, 4), &[("Slime Gel", 50), ("Regen Draught", 6)]),
+    drops("Jailer", (6, 12), &[("Rusty Key", 30), ("Gauntlets", 5)]),
+    drops("Stray Dog", (0, 1), &[]),
+    drops(
+        "Pickpocket",
+        (8, 20),
+        &[
+            ("Cloth Scraps", 30),
+            ("Rusty Key", 10),
+            ("Ring of Swiftness", 2),
+        ],
+    ),
+    drops(
+        "Thug",
+        (5, 12),
+        &[("Cloth Scraps", 30), ("Potion", 10), ("Leather Armor", 5)],
+    ),
+];
+
+/// what an enemy drops, by name. enemies in a group ("Wolf #2") share the table of their kind.
+pub fn drop_table(name: &str) -> Option<&'static DropTable> {
+    let name = name.split(" #").next().unwrap_or(name);
+
+    DROPS.iter().find(|table| table.enemy == name)
+}
+
+/// rolls the drops of an enemy, as (gold, items). enemies without a drop table drop gold based
+/// on how tough they are, and sometimes a potion.
+pub fn roll_drops(name: &str, stats: &Stats, rng: &mut StdRng) -> (u32, Vec<ItemKey>) {
+    let power = stats.power();
+    let (gold, items): ((u16, u16), &[(&str, u8)]) = match drop_table(name) {
+        Some(table) => (table.gold, table.items),
+        None => ((power / 4, power / 2), &[("Potion", 5)]),
+    };
+
+    let items = items
+        .iter()
+        .filter(|(_, chance)| rng.gen_range(0..100) < *chance)
+        .map(|(item, _)| ItemKey::new(item))
+        .collect();
+
+    (rng.gen_range(gold.0..=gold.1) as u32, items)
+}
@@ -0,0 +1,379 @@
+use crate::level::{zone_seed, Biome, Coords, Level};
+use crate::zone::{Zone, ZoneType};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+// the grammar tables are plain `&'static str` slices so they live in flash and cost no heap until
+// a zone is actually named.
+
+/// the end of the game, shown a line at a time once the seven keys open the gate. "{name}" is
+/// replaced with the players name.
+pub const ENDING: [&str; 7] = [
+    "The seven keys turn as one, and the sanctuary gate swings open.",
+    "Beyond it there is no forest, no desert, no ruin. Only a quiet hall lined with names.",
+    "Every one of them carried seven keys here, and every one of them left them at the door.",
+    "You lay yours beside theirs. The forest, the temple, the desert, the mountains,",
+    "the ruins, the dungeon, and the town fall silent behind you.",
+    "A new name is carved into the wall: {name}.",
+    "The worlds are beaten. Well done, {name}.",
+];
+
+/// roman numerals used to tell apart zones that ended up with the same name.
+const NUMERALS: [&str; 9] = ["II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X"];
+
+fn adjectives(biome: Biome) -> &'static [&'static str] {
+    match biome {
+        Biome::Forest => &[
+            "Whispering",
+            "Mossy",
+            "Tangled",
+            "Verdant",
+            "Shaded",
+            "Thorned",
+            "Fern-Choked",
+            "Elder",
+        ],
+        Biome::Temple => &[
+            "Hallowed",
+            "Gilded",
+            "Silent",
+            "Consecrated",
+            "Forsaken",
+            "Chanting",
+            "Veiled",
+            "Sacred",
+        ],
+        Biome::Desert => &[
+            "Sunken",
+            "Scorched",
+            "Shifting",
+            "Bleached",
+            "Windswept",
+            "Parched",
+            "Amber",
+            "Mirage",
+        ],
+        Biome::Mountains => &[
+            "Frostbitten",
+            "Craggy",
+            "Howling",
+            "Granite",
+            "Snowbound",
+            "Jagged",
+            "Eagle's",
+            "Lofty",
+        ],
+        Biome::Ruins => &[
+            "Crumbling",
+            "Toppled",
+            "Forgotten",
+            "Ivy-Bound",
+            "Shattered",
+            "Hollow",
+            "Ancient",
+            "Broken",
+        ],
+        Biome::Dungeon => &[
+            "Dripping",
+            "Shackled",
+            "Lightless",
+            "Rusted",
+            "Echoing",
+            "Rat-Gnawed",
+            "Iron",
+            "Damp",
+        ],
+        Biome::Town => &[
+            "Bustling",
+            "Cobbled",
+            "Lantern-Lit",
+            "Crooked",
+            "Merchant's",
+            "Quiet",
+            "Market",
+            "Old",
+        ],
+    }
+}
+
+fn nouns(kind: &ZoneType) -> &'static [&'static str] {
+    match kind {
+        ZoneType::Shelter => &["Hollow", "Refuge", "Lean-To", "Nook", "Hideaway", "Camp"],
+        ZoneType::OpenPath => &["Trail", "Road", "Way", "Meadow", "Expanse", "Crossing"],
+        ZoneType::Coredor => &[
+            "Passage",
+            "Colonnade",
+            "Corridor",
+            "Gallery",
+            "Hall",
+            "Causeway",
+        ],
+        ZoneType::Temple => &[
+            "Shrine",
+            "Chapel",
+            "Altar",
+            "Sanctum",
+            "Reliquary",
+            "Temple",
+        ],
+        ZoneType::Mountains => &["Ridge", "Peak", "Pass", "Bluff", "Crag", "Summit"],
+        ZoneType::Sanctuary => &["Haven", "Sanctuary", "Rest", "Oasis", "Grove", "Respite"],
+        ZoneType::TreasureRoom => &["Vault", "Hoard", "Trove", "Cache", "Treasury", "Stash"],
+        ZoneType::BossEntry => &["Gate", "Threshold", "Maw", "Rift", "Portal", "Breach"],
+        ZoneType::Wall => &["Wall"],
+    }
+}
+
+/// welcome banners. `{}` is replaced with the zones name.
+fn welcomes(kind: &ZoneType) -> &'static [&'static str] {
+    match kind {
+        ZoneType::Shelter => &[
+            "You duck into the {}. The wind can't find you here.",
+            "The {} offers a moment's rest.",
+        ],
+        ZoneType::OpenPath => &[
+            "You make your way along the {}.",
+            "The {} stretches out before you.",
+        ],
+        ZoneType::Coredor => &[
+            "Your footsteps echo through the {}.",
+            "The {} narrows around you.",
+        ],
+        ZoneType::Temple => &[
+            "You enter the {}. Something old is watching.",
+            "Candles still burn in the {}.",
+        ],
+        ZoneType::Mountains => &["You climb onto the {}.", "The air grows thin on the {}."],
+        ZoneType::Sanctuary => &[
+            "Welcome to the {}. No mob dares to near this place.",
+            "A calm settles over you in the {}.",
+        ],
+        ZoneType::TreasureRoom => &[
+            "Gold glints from the corners of the {}.",
+            "You have found the {}!",
+        ],
+        ZoneType::BossEntry => &[
+            "You feel an emence evil eminating from the {}.",
+            "The {} hums with a dreadful power.",
+        ],
+        ZoneType::Wall => &[""],
+    }
+}
+
+fn biome_descs(biome: Biome) -> &'static [&'static str] {
+    match biome {
+        Biome::Forest => &[
+            "Roots knot the ground and the canopy swallows the sky.",
+            "Birdsong falls silent as you pass.",
+        ],
+        Biome::Temple => &[
+            "Faded murals of forgotten gods line every surface.",
+            "The smell of old incense hangs in the air.",
+        ],
+        Biome::Desert => &[
+            "Sand hisses across the ground in the hot wind.",
+            "The sun bleaches everything it touches.",
+        ],
+        Biome::Mountains => &[
+            "Loose scree shifts under your boots.",
+            "Far below, clouds drift between the peaks.",
+        ],
+        Biome::Ruins => &[
+            "Broken pillars jut from the rubble like old bones.",
+            "Whatever city stood here fell long ago.",
+        ],
+        Biome::Dungeon => &[
+            "Water drips somewhere in the dark.",
+            "Chains rattle, though nothing seems to move them.",
+        ],
+        Biome::Town => &[
+            "Shuttered windows watch the empty streets.",
+            "The smell of bread and smoke drifts by.",
+        ],
+    }
+}
+
+fn kind_descs(kind: &ZoneType) -> &'static [&'static str] {
+    match kind {
+        ZoneType::Shelter => &["It would be a good place to rest."],
+        ZoneType::OpenPath => &["Paths lead off in several directions."],
+        ZoneType::Coredor => &["There is only one way through, and it is narrow."],
+        ZoneType::Temple => &["An altar stands at the center, worn smooth by prayer."],
+        ZoneType::Mountains => &["One wrong step could be your last."],
+        ZoneType::Sanctuary => &["A warp portal flickers softly beside a weathered sign."],
+        ZoneType::TreasureRoom => &["Many have come here for riches. Not all have left."],
+        ZoneType::BossEntry => &["A portal leads somewhere you may not return from."],
+        ZoneType::Wall => &[""],
+    }
+}
+
+/// composes names, welcome banners, and descriptions for the zones of a level. the namer is
+/// seeded from the level so the same level always gets the same names, and it remembers the
+/// names it has handed out so no two zones in a level share one.
+pub struct ZoneNamer {
+    rng: StdRng,
+    biome: Biome,
+    used: HashSet<Arc<str>>,
+}
+
+impl ZoneNamer {
+    pub fn new(seed: u64, biome: Biome) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            biome,
+            used: HashSet::new(),
+        }
+    }
+
+    fn pick(&mut self, table: &'static [&'static str]) -> &'static str {
+        table.choose(&mut self.rng).copied().unwrap_or_default()
+    }
+
+    /// generates a name that hasn't been used yet in this level.
+    pub fn name(&mut self, kind: &ZoneType) -> Arc<str> {
+        let adjectives = adjectives(self.biome);
+        let nouns = nouns(kind);
+
+        // a few random tries usually finds a free name, after that count up through the
+        // numerals.
+        let mut base = String::new();
+
+        for _ in 0..adjectives.len() {
+            let adjective = self.pick(adjectives);
+            // some words are both, there's no "Hollow Hollow".
+            let choices: Vec<&str> = nouns.iter().copied().filter(|n| *n != adjective).collect();
+            let noun = choices.choose(&mut self.rng).copied().unwrap_or_default();
+            base = format!("{adjective} {noun}");
+
+            if !self.used.contains(base.as_str()) {
+                break;
+            }
+        }
+
+        // once the numerals run out, fall back to the number of names handed out so far. it goes
+        // up with every name so it can't be taken already.
+        let name: Arc<str> = std::iter::once(base.clone())
+            .chain(NUMERALS.iter().map(|n| format!("{base} {n}")))
+            .find(|name| !self.used.contains(name.as_str()))
+            .unwrap_or_else(|| format!("{base} {}", self.used.len() + 1))
+            .into();
+
+        self.used.insert(name.clone());
+
+        name
+    }
+
+    /// names and describes the zone based on its type.
+    pub fn dress(&mut self, zone: &mut Zone) {
+        if zone.feild_type == ZoneType::Wall {
+            return;
+        }
+
+        let kind = zone.feild_type.clone();
+        let name = self.name(&kind);
+        let welcome = self.pick(welcomes(&kind)).replace("{}", &name);
+        let desc = format!(
+            "{} {}",
+            self.pick(biome_descs(self.biome)),
+            self.pick(kind_descs(&kind))
+        );
+
+        zone.name(&name);
+        zone.welcome(&welcome);
+        zone.desc(&desc);
+    }
+}
+
+/// the compass direction from one zone to another. uses the same axes as the NORTH, SOUTH, EAST,
+/// and WEST constants in `level`.
+pub fn compass(from: Coords, to: Coords) -> &'static str {
+    let dx = to.0 as isize - from.0 as isize;
+    let dy = to.1 as isize - from.1 as isize;
+
+    // only count an axis if it is at least half as far as the other, that way "north-west" means
+    // roughly diagonal.
+    let ns = if dy.abs() * 2 < dx.abs() {
+        ""
+    } else if dy < 0 {
+        "north"
+    } else if dy > 0 {
+        "south"
+    } else {
+        ""
+    };
+    let ew = if dx.abs() * 2 < dy.abs() {
+        ""
+    } else if dx > 0 {
+        "west"
+    } else if dx < 0 {
+        "east"
+    } else {
+        ""
+    };
+
+    match (ns, ew) {
+        ("", "") => "right here",
+        ("north", "east") => "north-east",
+        ("north", "west") => "north-west",
+        ("south", "east") => "south-east",
+        ("south", "west") => "south-west",
+        (dir, "") | ("", dir) => dir,
+        _ => unreachable!("every pair of directions is handled above."),
+    }
+}
+
+/// writes the text of the sign that stands in a Sanctuary. the sign tells the player about the
+/// level's biome and size, points toward the boss portal, and passes on rumors of the treasure
+/// rooms and temples nearby.
+pub fn sign(level: &Level, at: Coords) -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(zone_seed(level.seed, at));
+    let (w, h) = (level.dim.0 as usize, level.dim.1 as usize);
+    let size = match w * h {
+        0..=499 => "modest",
+        500..=649 => "large",
+        _ => "vast",
+    };
+    let portal = (level.boss_portal.0 as usize, level.boss_portal.1 as usize);
+
+    let mut lines = vec![
+        format!("Welcome, traveler, to the {}.", level.biome),
+        format!(
+            "These lands are {size}, some {} by {} zones.",
+            (w + 2) / 5 * 5,
+            (h + 2) / 5 * 5
+        ),
+        format!("Evil stirs to the {}.", compass(at, portal)),
+    ];
+
+    let mut sights: Vec<Coords> = (0..h)
+        .flat_map(|y| (0..w).map(move |x| (x, y)))
+        .filter(|c| {
+            matches!(
+                level[*c].feild_type,
+                ZoneType::TreasureRoom | ZoneType::Temple
+            )
+        })
+        .collect();
+    sights.shuffle(&mut rng);
+
+    for sight in sights.iter().take(3) {
+        let what = match level[*sight].feild_type {
+            ZoneType::TreasureRoom => "treasure",
+            _ => "an old temple",
+        };
+        let how_far = if sight.0.abs_diff(at.0) + sight.1.abs_diff(at.1) <= 8 {
+            "not far"
+        } else {
+            "far"
+        };
+
+        lines.push(format!(
+            "Rumor has it there is {what} {how_far} to the {}.",
+            compass(at, *sight)
+        ));
+    }
+
+    lines
+}
@@ -1,8 +1,20 @@
+use std::fmt;
 use std::sync::Arc;
+use std::time::Instant;
 
+use anyhow::bail;
 use rand::seq::SliceRandom;
 
-use crate::battle_logic::{BattleState, Spell};
+use crate::battle_logic::{healed, lifeness, Ally, Battlable, Spell};
+use crate::crafting;
+use crate::damage::{self, Damage};
+use crate::enemies::Lifeness;
+use crate::experience::{self, MANA_PER_LEVEL, MAX_LEVEL, UNLOCKS};
+use crate::inventory::Inventory;
+use crate::items::{self, Category, Item, ItemKey};
+use crate::keys::KeyRing;
+use crate::score::ScoreCard;
+use crate::spells::{self, Mana};
 
 const DEFAULT_NAMES: [&str; 10] = [
     "Calacuda",    // me
@@ -23,21 +35,68 @@ const DEFAULT_NAMES: [&str; 10] = [
 pub trait StatusEffect {
     /// returns what the effect should be referred to as when displayed to the player. (poison,
     /// slowness, burn, etc)
-    fn display_name(&mut self) -> Arc<str>;
+    fn display_name(&self) -> Arc<str>;
 
     /// returns true if the affect is still affecting the entity
-    fn in_effect(&mut self) -> bool;
+    fn in_effect(&self) -> bool;
 
     /// returns the buff that should be applied to the entity
-    fn get_buff(&mut self) -> Buff;
+    fn get_buff(&self) -> Buff;
 
-    /// a generic step function to update the internal state of the effect. used to handle generic
-    /// house keeping for the effect, and to do any calculation the effect needs to do. for
-    /// example: if the affect of the affect gets more/less sevear over time, do that math here.
-    fn step(&mut self, battle: BattleState) -> anyhow::Result<()>;
+    /// the chance (0.0 to 1.0) that the entity's attacks land at all. (blindness, etc)
+    fn accuracy(&self) -> f32 {
+        1.0
+    }
+
+    /// how the effect combines with another effect of the same name.
+    fn stacking(&self) -> Stacking {
+        Stacking::Refresh
+    }
+
+    /// how many turns the effect has left.
+    fn turns(&self) -> u8;
+
+    /// how strong the effect is. (damage per turn, etc)
+    fn potency(&self) -> u8 {
+        0
+    }
+
+    /// folds a newer application of the same effect into this one. see stacking.
+    #[allow(unused_variables)]
+    fn merge(&mut self, turns: u8, potency: u8) {}
+
+    /// a generic step function to update the internal state of the effect. called at the start
+    /// of the affected entity's turn in battle, and every so often while exploring. used to
+    /// handle generic house keeping for the effect, and to do any calculation the effect needs to
+    /// do. for example: if the affect of the affect gets more/less sevear over time, do that math
+    /// here. returns what the effect does to the entity this turn.
+    fn step(&mut self) -> Tick;
+}
+
+/// what a status effect does to the entity it's on when it ticks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tick {
+    /// health lost
+    pub damage: u8,
+    /// health restored
+    pub heal: u8,
+    /// true if the entity loses its turn. (stun, etc)
+    pub skip_turn: bool,
+}
+
+/// how a status effect reacts to being applied again while it's still in effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stacking {
+    /// the effect lasts as long as the longer of the two, and as strong as the stronger.
+    Refresh,
+    /// the effect lasts as long as the longer of the two, and the potencies add up.
+    Intensify,
+    /// both effects run side by side.
+    Separate,
 }
 
 /// how a buff should impact stats.
+#[derive(Debug, Clone, PartialEq)]
 pub enum BuffType {
     /// indicates the ussage of a log function to generate the buff (better at lower levels).
     Log(i8),
@@ -51,6 +110,7 @@ pub enum BuffType {
 }
 
 /// buff/debuff for players & enemies
+#[derive(Debug, Clone, PartialEq)]
 pub struct Buff {
     pub hp: BuffType,
     pub str: BuffType,
@@ -60,6 +120,85 @@ pub struct Buff {
     pub speed: BuffType,
 }
 
+impl Buff {
+    /// a buff that changes nothing. handy as a base for buffs that only touch a few stats.
+    pub fn none() -> Self {
+        Self {
+            hp: BuffType::Const(0),
+            str: BuffType::Const(0),
+            mg_str: BuffType::Const(0),
+            def: BuffType::Const(0),
+            mg_def: BuffType::Const(0),
+            speed: BuffType::Const(0),
+        }
+    }
+}
+
+/// the base of the exponent used by BuffType::Exp.
+const EXP_BASE: f32 = 1.1;
+
+impl BuffType {
+    /// how much a Log or Exp buff is worth at a level. Log(n) is worth n * ln(1 + level) and
+    /// Exp(n) is worth n * (1.1^level - 1), so Log buffs are better until around level 14, after
+    /// which Exp buffs take over. Const and Multiplier buffs don't scale so they are worth 0 here.
+    fn scaled(&self, level: u8) -> f32 {
+        match self {
+            BuffType::Log(n) => *n as f32 * (1.0 + level as f32).ln(),
+            BuffType::Exp(n) => *n as f32 * (EXP_BASE.powi(level as i32) - 1.0),
+            BuffType::Const(_) | BuffType::Multiplier(_) => 0.0,
+        }
+    }
+}
+
+/// works out a single stat from its base value and the buffs on it. the buffs are applied in
+/// this order:
+///
+/// 1. every Const is added to the base value.
+/// 2. every Log and Exp is scaled by `level` (see BuffType::scaled) and added.
+/// 3. the total is multiplied by every Multiplier.
+///
+/// the result is rounded and clamped to fit in a u8. nothing is clamped until the end, so a big
+/// debuff can be cancelled out by a big buff.
+fn stat<'a>(base: u8, buffs: impl Iterator<Item = &'a BuffType> + Clone, level: u8) -> u8 {
+    let consts: f32 = buffs
+        .clone()
+        .map(|buff| match buff {
+            BuffType::Const(n) => *n as f32,
+            _ => 0.0,
+        })
+        .sum();
+    let scaled: f32 = buffs.clone().map(|buff| buff.scaled(level)).sum();
+    let multiplier: f32 = buffs
+        .map(|buff| match buff {
+            BuffType::Multiplier(n) => *n,
+            _ => 1.0,
+        })
+        .product();
+
+    ((base as f32 + consts + scaled) * multiplier)
+        .round()
+        .clamp(0.0, u8::MAX as f32) as u8
+}
+
+/// folds buffs into a set of stats. `level` is the level of whoever the stats belong to, used
+/// to scale Log and Exp buffs. see `stat` for how the buffs add up.
+pub fn buffed<'a>(base: &Stats, buffs: impl Iterator<Item = &'a Buff> + Clone, level: u8) -> Stats {
+    Stats {
+        hp: stat(base.hp, buffs.clone().map(|buff| &buff.hp), level),
+        str: stat(base.str, buffs.clone().map(|buff| &buff.str), level),
+        mg_str: stat(base.mg_str, buffs.clone().map(|buff| &buff.mg_str), level),
+        def: stat(base.def, buffs.clone().map(|buff| &buff.def), level),
+        mg_def: stat(base.mg_def, buffs.clone().map(|buff| &buff.mg_def), level),
+        speed: stat(base.speed, buffs.map(|buff| &buff.speed), level),
+    }
+}
+
+/// the buffs from a list of status effects.
+pub fn status_buffs(status: &[Box<dyn StatusEffect>]) -> Vec<Buff> {
+    status.iter().map(|effect| effect.get_buff()).collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Stats {
     /// how much health the player has
     pub hp: u8,
@@ -76,28 +215,103 @@ pub struct Stats {
     pub speed: u8,
 }
 
+impl Stats {
+    /// a rough single number measure of how dangerous an entity is. used to warn players about
+    /// fights they aren't ready for.
+    pub fn power(&self) -> u16 {
+        self.hp as u16 / 5
+            + self.str as u16
+            + self.mg_str as u16
+            + self.def as u16
+            + self.mg_def as u16
+            + self.speed as u16
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hp: {}, str: {}, mg_str: {}, def: {}, mg_def: {}, speed: {}",
+            self.hp, self.str, self.mg_str, self.def, self.mg_def, self.speed
+        )
+    }
+}
+
 /// where does the entity hold/wear this Equipment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EquipType {
     Helmet,
     Body,
     Pants,
     Hands,
+    /// covers the head, body, and legs all at once. (robes, plate, etc)
     FullBody,
     Pendant,
     Title,
     Ring,
     WeaponHand,
+    /// a weapon that takes both hands, so nothing can be held in the off hand.
+    TwoHanded,
     OffHand,
 }
 
+impl fmt::Display for EquipType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EquipType::Helmet => write!(f, "helmet"),
+            EquipType::Body => write!(f, "body"),
+            EquipType::Pants => write!(f, "pants"),
+            EquipType::Hands => write!(f, "hands"),
+            EquipType::FullBody => write!(f, "full body"),
+            EquipType::Pendant => write!(f, "pendant"),
+            EquipType::Title => write!(f, "title"),
+            EquipType::Ring => write!(f, "ring"),
+            EquipType::WeaponHand => write!(f, "weapon"),
+            EquipType::TwoHanded => write!(f, "two handed weapon"),
+            EquipType::OffHand => write!(f, "off hand"),
+        }
+    }
+}
+
 /// equipment for the player, NPCs, or Enemies
+#[derive(Debug, Clone, PartialEq)]
 pub struct Equipment {
+    /// the item this Equipment is, so it can go back in the inventory when taken off.
+    pub key: ItemKey,
     /// where does the entity hold/wear this Equipment
     pub kind: EquipType,
     /// the buff to be applied to the entity who equips this Equipment
     pub buff: Buff,
 }
 
+impl Item for Equipment {
+    fn key(&self) -> ItemKey {
+        self.key.clone()
+    }
+
+    fn name(&self) -> Arc<str> {
+        self.key.name.clone()
+    }
+
+    fn desc(&self) -> Arc<str> {
+        Arc::from(items::lookup(&self.key.name).map_or("", |def| def.desc))
+    }
+
+    fn category(&self) -> Category {
+        Category::Equipment
+    }
+
+    fn value(&self) -> u16 {
+        items::lookup(&self.key.name).map_or(0, |def| def.value)
+    }
+
+    fn equipment(&self) -> Option<Equipment> {
+        Some(self.clone())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Equipped {
     pub helmet: Option<Equipment>,
     pub body: Option<Equipment>,
@@ -107,6 +321,7 @@ pub struct Equipped {
     pub pendant: Option<Equipment>,
     pub title: Option<Equipment>,
     pub ring: Option<Equipment>,
+    /// one handed and two handed weapons both go here.
     pub main_weapon: Option<Equipment>,
     pub off_hand: Option<Equipment>,
 }
@@ -126,9 +341,88 @@ impl Equipped {
             off_hand: None,
         }
     }
-}
 
-// TODO: implement Item trait for Equipment
+    /// everything that is equipped.
+    pub fn iter(&self) -> impl Iterator<Item = &Equipment> + Clone {
+        [
+            &self.helmet,
+            &self.body,
+            &self.pants,
+            &self.hands,
+            &self.full_body,
+            &self.pendant,
+            &self.title,
+            &self.ring,
+            &self.main_weapon,
+            &self.off_hand,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// the slot a kind of equipment goes in.
+    fn slot(&mut self, kind: EquipType) -> &mut Option<Equipment> {
+        match kind {
+            EquipType::Helmet => &mut self.helmet,
+            EquipType::Body => &mut self.body,
+            EquipType::Pants => &mut self.pants,
+            EquipType::Hands => &mut self.hands,
+            EquipType::FullBody => &mut self.full_body,
+            EquipType::Pendant => &mut self.pendant,
+            EquipType::Title => &mut self.title,
+            EquipType::Ring => &mut self.ring,
+            EquipType::WeaponHand | EquipType::TwoHanded => &mut self.main_weapon,
+            EquipType::OffHand => &mut self.off_hand,
+        }
+    }
+
+    /// puts on a piece of equipment. returns everything that had to come off to make room: what
+    /// was in the slot already, any armor a FullBody piece covers (or the FullBody piece in the
+    /// way of some armor), and the off hand if a two handed weapon goes on (or the two handed
+    /// weapon if something goes in the off hand).
+    pub fn equip(&mut self, piece: Equipment) -> Vec<Equipment> {
+        let mut off = Vec::new();
+
+        match piece.kind {
+            EquipType::FullBody => {
+                off.extend(self.helmet.take());
+                off.extend(self.body.take());
+                off.extend(self.pants.take());
+            }
+            EquipType::Helmet | EquipType::Body | EquipType::Pants => {
+                off.extend(self.full_body.take())
+            }
+            EquipType::TwoHanded => off.extend(self.off_hand.take()),
+            EquipType::OffHand if matches!(&self.main_weapon, Some(weapon) if weapon.kind == EquipType::TwoHanded) => {
+                off.extend(self.main_weapon.take())
+            }
+            _ => {}
+        }
+
+        off.extend(self.slot(piece.kind).replace(piece));
+
+        off
+    }
+
+    /// takes off whatever is in a slot. `slot` is the slots name. (see EquipType's Display)
+    pub fn unequip(&mut self, slot: &str) -> Option<Equipment> {
+        let kind = match slot.to_lowercase().replace('_', " ").as_str() {
+            "helmet" | "head" => EquipType::Helmet,
+            "body" | "armor" => EquipType::Body,
+            "pants" | "legs" => EquipType::Pants,
+            "hands" | "gloves" => EquipType::Hands,
+            "full body" => EquipType::FullBody,
+            "pendant" | "neck" => EquipType::Pendant,
+            "title" => EquipType::Title,
+            "ring" => EquipType::Ring,
+            "weapon" | "main weapon" | "two handed weapon" => EquipType::WeaponHand,
+            "off hand" | "shield" => EquipType::OffHand,
+            _ => return None,
+        };
+
+        self.slot(kind).take()
+    }
+}
 
 // TODO: finish player struct.
 
@@ -136,20 +430,44 @@ impl Equipped {
 pub struct Player {
     /// the name of the player character
     pub name: Arc<str>,
-    /// the players score
+    /// the players score, the total of their score card.
     pub score: u16, // could likely be a u8 but using u16 to be safe.
-    // the player has access to all items just some have a count of zero. but this is still here just in case
-    // inventory: Vec<Box<dyn Item>>,
+    /// where the players score comes from.
+    pub card: ScoreCard,
+    /// the players level. Log and Exp buffs scale with it.
+    pub level: u8,
+    /// xp earned towards the next level. (see experience::to_next)
+    pub xp: u32,
+    /// training points left to spend on growing a stat.
+    pub points: u8,
+    /// how the players stats grow with their level. (see experience::growth)
+    pub growth: Buff,
+    /// the players money.
+    pub gold: u32,
+    /// the player has access to all items just some have a count of zero.
+    pub inventory: Inventory,
+    /// the biome keys the player has won.
+    pub keys: KeyRing,
     /// the spells the player knows.
     pub spells: Vec<Box<dyn Spell>>,
+    /// the crafting recipes the player knows, by the name of what they make.
+    pub recipes: Vec<Arc<str>>,
+    /// the players mana and spell cooldowns.
+    pub mana: Mana,
     /// the players current stats.
     pub stats: Stats,
+    /// the players current health. (stats.hp is their max health)
+    pub hp: u8,
     /// the equipment the player has equipped.
     pub gear: Equipped,
     /// anny buffs that have been aplyed by spells, potions, etc
     pub bufs: Vec<Buff>,
     /// a list of status effects affectign the player.
     pub status: Vec<Box<dyn StatusEffect>>,
+    /// the companions travelling with the player. (see allies::MAX_PARTY)
+    pub party: Vec<Box<dyn Ally>>,
+    /// when the player set out, for timing how long they take to beat the game.
+    pub started: Instant,
 }
 
 impl Player {
@@ -161,7 +479,18 @@ impl Player {
                 .unwrap_or(&DEFAULT_NAMES.choose(&mut rng).unwrap_or(&DEFAULT_NAMES[0]))
                 .into(),
             score: 0,
+            card: ScoreCard::new(),
+            level: 1,
+            xp: 0,
+            points: 0,
+            growth: experience::growth(),
+            gold: 0,
+            inventory: Inventory::new(),
+            keys: KeyRing::new(),
             spells: Vec::new(),
+            recipes: crafting::starting(),
+            mana: Mana::new(10),
+            hp: 20,
             stats: Stats {
                 hp: 20,
                 str: 5,
@@ -173,12 +502,331 @@ impl Player {
             gear: Equipped::new(),
             bufs: Vec::new(),
             status: Vec::new(),
+            party: Vec::new(),
+            started: Instant::now(),
         }
     }
 
     pub fn name(&mut self, name: &str) {
         self.name = Arc::from(name);
     }
+
+    /// the players stats at their level, before gear, buffs, and status effects. growth is
+    /// counted from the levels gained, so a level 1 player has their starting stats.
+    pub fn base_stats(&self) -> Stats {
+        buffed(
+            &self.stats,
+            std::iter::once(&self.growth),
+            self.level.saturating_sub(1),
+        )
+    }
+
+    /// the players base stats next to their effective stats (after gear, buffs, and status
+    /// effects), one stat per line.
+    pub fn stat_sheet(&self) -> Vec<String> {
+        let mut sheet = vec![
+            format!(
+                "{} (level {}, {}/{} xp)",
+                self.name,
+                self.level,
+                self.xp,
+                experience::to_next(self.level)
+            ),
+            format!("{:<8}{:>6}{:>6}", "", "base", "now"),
+        ];
+
+        for (name, base, now) in rows(&self.base_stats(), &self.get_stats()) {
+            let diff = match now as i16 - base as i16 {
+                0 => String::new(),
+                d => format!(" ({d:+})"),
+            };
+
+            sheet.push(format!("{name:<8}{base:>6}{now:>6}{diff}"));
+        }
+
+        if self.points > 0 {
+            sheet.push(format!("{} training points to spend.", self.points));
+        }
+
+        sheet.push(format!(
+            "{} gold, {} points (see score)",
+            self.gold, self.score
+        ));
+
+        sheet
+    }
+
+    /// gives the player xp, levelling them up as many times as it covers. returns what happened,
+    /// one line per event.
+    pub fn gain_xp(&mut self, xp: u32) -> Vec<String> {
+        let mut msgs = vec![format!("{} gains {xp} xp.", self.name)];
+        let points = self.points;
+
+        self.xp += xp;
+
+        while self.level < MAX_LEVEL && self.xp >= experience::to_next(self.level) {
+            let before = self.base_stats();
+
+            self.xp -= experience::to_next(self.level);
+            self.level += 1;
+            self.points += 1;
+            self.mana.max = self.mana.max.saturating_add(MANA_PER_LEVEL);
+            msgs.push(format!("{} reached level {}!", self.name, self.level));
+
+            let after = self.base_stats();
+
+            for (name, before, after) in rows(&before, &after) {
+                if after != before {
+                    msgs.push(format!("  {name} {before} -> {after}"));
+                }
+            }
+
+            // the new health comes already healed.
+            self.heal(after.hp.saturating_sub(before.hp));
+
+            let level = self.level;
+
+            for (_, spell) in UNLOCKS.iter().filter(|(at, _)| *at == level) {
+                if self.learn(spell) {
+                    msgs.push(format!("{} learned to cast {spell}!", self.name));
+                }
+            }
+        }
+
+        if self.points > points {
+            msgs.push(format!(
+                "{} training points to spend. (train <stat>)",
+                self.points
+            ));
+        }
+
+        msgs
+    }
+
+    /// spends a training point to make a stat grow faster. the point counts for every level
+    /// already gained, not just the ones to come.
+    pub fn train(&mut self, stat: &str) -> anyhow::Result<()> {
+        if self.points == 0 {
+            bail!("you have no training points. level up to earn more.");
+        }
+
+        let Some(factor) = experience::factor(&mut self.growth, stat) else {
+            bail!("there is no stat called \"{stat}\". (hp, str, mg_str, def, mg_def, or speed)");
+        };
+
+        experience::train(factor);
+        self.points -= 1;
+
+        Ok(())
+    }
+
+    /// the players stats if they were wearing `gear` instead of what they have on.
+    fn stats_with(&self, gear: &Equipped) -> Stats {
+        let status = status_buffs(&self.status);
+        let buffs = gear
+            .iter()
+            .map(|equipment| &equipment.buff)
+            .chain(self.bufs.iter())
+            .chain(status.iter());
+
+        buffed(&self.base_stats(), buffs, self.level)
+    }
+
+    /// finds a piece of equipment in the inventory by name.
+    fn gear_for(&self, name: &str) -> anyhow::Result<(ItemKey, Equipment)> {
+        let Some(key) = self.inventory.find(name) else {
+            bail!("you don't have a \"{name}\". try inventory");
+        };
+        let Some(piece) = items::make(&key).and_then(|item| item.equipment()) else {
+            bail!("you can't equip the {}.", key.name);
+        };
+
+        Ok((key, piece))
+    }
+
+    /// how the players stats would change if they wore `gear`, one line per stat that changes.
+    fn changes(&self, gear: &Equipped) -> Vec<String> {
+        let lines: Vec<String> = rows(&self.get_stats(), &self.stats_with(gear))
+            .into_iter()
+            .filter(|(_, now, then)| now != then)
+            .map(|(name, now, then)| {
+                format!("  {name} {now} -> {then} ({:+})", then as i16 - now as i16)
+            })
+            .collect();
+
+        match lines.is_empty() {
+            true => vec!["  no change.".to_string()],
+            false => lines,
+        }
+    }
+
+    /// compares a piece of equipment in the inventory against what the player has on.
+    pub fn compare(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        let (key, piece) = self.gear_for(name)?;
+        let kind = piece.kind;
+        let mut gear = self.gear.clone();
+        let off: Vec<String> = gear
+            .equip(piece)
+            .iter()
+            .map(|piece| piece.name().to_string())
+            .collect();
+
+        let mut lines = match off.is_empty() {
+            true => vec![format!("{} ({kind}):", key.name)],
+            false => vec![format!(
+                "{} ({kind}) instead of {}:",
+                key.name,
+                off.join(", ")
+            )],
+        };
+        lines.extend(self.changes(&gear));
+
+        Ok(lines)
+    }
+
+    /// puts on a piece of equipment from the inventory. anything it replaces goes back in the
+    /// inventory. returns what happened, one line per event.
+    pub fn equip(&mut self, name: &str) -> anyhow::Result<Vec<String>> {
+        let (key, piece) = self.gear_for(name)?;
+        let mut gear = self.gear.clone();
+        let off = gear.equip(piece);
+        // the piece being put on frees up its own slot.
+        let room = (self.inventory.slots as usize + 1).saturating_sub(self.inventory.used());
+
+        if off.len() > room {
+            bail!("there is no room in your pack for the gear you would have to take off.");
+        }
+
+        let mut msgs = self.changes(&gear);
+        self.inventory.remove(&key, 1)?;
+
+        for piece in off {
+            self.inventory.put_back(&piece)?;
+            msgs.push(format!("you take off the {}.", piece.name()));
+        }
+
+        self.gear = gear;
+        self.hp = self.hp.min(self.get_max_hp());
+        msgs.push(format!("you equip the {}.", key.name));
+
+        Ok(msgs)
+    }
+
+    /// takes off whatever is in a slot and puts it in the inventory.
+    pub fn unequip(&mut self, slot: &str) -> anyhow::Result<String> {
+        if self.inventory.used() >= self.inventory.slots as usize {
+            bail!("there is no room in your pack.");
+        }
+
+        let Some(piece) = self.gear.unequip(slot) else {
+            bail!("you have nothing on your {slot}.");
+        };
+
+        self.inventory.put_back(&piece)?;
+        self.hp = self.hp.min(self.get_max_hp());
+
+        Ok(format!("you take off the {}.", piece.name()))
+    }
+
+    /// what the player has on, one line per piece.
+    pub fn gear_sheet(&self) -> Vec<String> {
+        let sheet: Vec<String> = self
+            .gear
+            .iter()
+            .map(|piece| format!("{}: {}", piece.kind, piece.name()))
+            .collect();
+
+        match sheet.is_empty() {
+            true => vec!["you are wearing nothing special.".to_string()],
+            false => sheet,
+        }
+    }
+
+    /// returns the spell the player knows by that name, if they know it.
+    pub fn spell(&self, name: &str) -> Option<&dyn Spell> {
+        self.spells
+            .iter()
+            .find(|spell| spell.name().eq_ignore_ascii_case(name))
+            .map(|spell| spell.as_ref())
+    }
+
+    /// teaches the player a spell from the spell book. returns false if they already know it or
+    /// there is no such spell.
+    pub fn learn(&mut self, name: &str) -> bool {
+        if self.spell(name).is_some() {
+            return false;
+        }
+
+        match spells::learn(name) {
+            Some(spell) => {
+                self.spells.push(spell);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// true if the player knows how to make an item.
+    pub fn knows_recipe(&self, name: &str) -> bool {
+        self.recipes
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(name))
+    }
+
+    /// teaches the player a crafting recipe. returns false if they already know it.
+    pub fn learn_recipe(&mut self, name: &str) -> bool {
+        if self.knows_recipe(name) {
+            return false;
+        }
+
+        self.recipes.push(Arc::from(name));
+        true
+    }
 }
 
-// TODO: impl battle_logic::Battlable for Player
+/// pairs up each stat of two stat blocks, by name.
+fn rows(a: &Stats, b: &Stats) -> [(&'static str, u8, u8); 6] {
+    [
+        ("hp", a.hp, b.hp),
+        ("str", a.str, b.str),
+        ("mg_str", a.mg_str, b.mg_str),
+        ("def", a.def, b.def),
+        ("mg_def", a.mg_def, b.mg_def),
+        ("speed", a.speed, b.speed),
+    ]
+}
+
+impl Battlable for Player {
+    fn get_name(&self) -> Arc<str> {
+        self.name.clone()
+    }
+
+    fn get_hp(&self) -> u8 {
+        self.hp
+    }
+
+    fn get_stats(&self) -> Stats {
+        self.stats_with(&self.gear)
+    }
+
+    fn apply_damage(&mut self, dmg: Damage) -> Lifeness {
+        self.hp = damage::apply(self.hp, &dmg);
+
+        lifeness(self.hp)
+    }
+
+    fn heal(&mut self, amount: u8) -> u8 {
+        let (hp, restored) = healed(self.hp, self.get_max_hp(), amount);
+        self.hp = hp;
+
+        restored
+    }
+
+    fn status_effects(&mut self) -> &mut Vec<Box<dyn StatusEffect>> {
+        &mut self.status
+    }
+
+    fn mana(&mut self) -> Option<&mut Mana> {
+        Some(&mut self.mana)
+    }
+}
@@ -0,0 +1,129 @@
+use crate::loot::{self, Rarity};
+use crate::player::Stats;
+use serde::{Deserialize, Serialize};
+
+// every kind of points is measured against something that doesn't change between worlds (the
+// share of a level explored, how tough an enemy is next to the player, how rare an item is) so
+// scores from different seeds and biomes can be compared.
+
+/// the points for exploring every zone of a level, however big it is.
+pub const EXPLORE_POINTS: u32 = 1000;
+/// the points for defeating an enemy as strong as the player. weaker enemies are worth less and
+/// stronger ones more, up to MAX_FIGHT_POINTS.
+pub const FIGHT_POINTS: u32 = 20;
+const MAX_FIGHT_POINTS: u32 = 100;
+/// the points for beating a biomes boss.
+pub const BOSS_POINTS: u32 = 1500;
+/// battles won in this many turns or fewer earn QUICK_POINTS for each turn to spare.
+pub const QUICK_TURNS: u16 = 3;
+const QUICK_POINTS: u32 = 10;
+/// the bonus for never falling in battle, as a percent of the rest of the score. only counts
+/// once a boss has been beaten.
+pub const NO_DEATH_BONUS: u32 = 25;
+
+/// the points for finding an item.
+fn treasure_points(rarity: Rarity) -> u32 {
+    match rarity {
+        Rarity::Common => 5,
+        Rarity::Uncommon => 15,
+        Rarity::Rare => 40,
+        Rarity::Legendary => 100,
+    }
+}
+
+/// where the players score comes from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreCard {
+    /// points for discovering zones, in thousandths of a point so a level is worth the same
+    /// however many zones it splits EXPLORE_POINTS between.
+    explored: u32,
+    /// points for defeating enemies.
+    pub combat: u32,
+    /// points for finding items.
+    pub treasure: u32,
+    /// points for beating bosses.
+    pub bosses: u32,
+    /// points for winning battles quickly.
+    pub quick: u32,
+    /// how many times the player has fallen in battle.
+    pub deaths: u16,
+}
+
+impl ScoreCard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the player set foot in a new zone of a level with `zones` zones to explore.
+    pub fn discover(&mut self, zones: usize) {
+        self.explored += EXPLORE_POINTS * 1000 / zones.max(1) as u32;
+    }
+
+    /// points for discovering zones.
+    pub fn exploration(&self) -> u32 {
+        self.explored / 1000
+    }
+
+    /// the player defeated an enemy with stats `enemy` while they had stats `player`.
+    pub fn defeat(&mut self, enemy: &Stats, player: &Stats) {
+        let points = FIGHT_POINTS * enemy.power() as u32 / player.power().max(1) as u32;
+
+        self.combat += points.clamp(1, MAX_FIGHT_POINTS);
+    }
+
+    /// the player picked up an item.
+    pub fn find(&mut self, item: &str) {
+        self.treasure += treasure_points(loot::rarity(item));
+    }
+
+    /// the player beat a biomes boss.
+    pub fn boss(&mut self) {
+        self.bosses += BOSS_POINTS;
+    }
+
+    /// the player won a battle in `turns` turns.
+    pub fn won(&mut self, turns: u16) {
+        self.quick += QUICK_TURNS.saturating_sub(turns.saturating_sub(1)) as u32 * QUICK_POINTS;
+    }
+
+    /// the player fell in battle.
+    pub fn died(&mut self) {
+        self.deaths = self.deaths.saturating_add(1);
+    }
+
+    /// the bonus for never falling in battle.
+    pub fn bonus(&self) -> u32 {
+        match self.deaths == 0 && self.bosses > 0 {
+            true => self.subtotal() * NO_DEATH_BONUS / 100,
+            false => 0,
+        }
+    }
+
+    fn subtotal(&self) -> u32 {
+        self.exploration() + self.combat + self.treasure + self.bosses + self.quick
+    }
+
+    /// the players score.
+    pub fn total(&self) -> u16 {
+        (self.subtotal() + self.bonus()).min(u16::MAX as u32) as u16
+    }
+
+    /// where the score comes from, as the player sees it.
+    pub fn breakdown(&self) -> Vec<String> {
+        let bonus = match self.deaths {
+            0 if self.bosses == 0 => "  no deaths   (beat a boss first)".to_string(),
+            0 => format!("  no deaths   {:>6} (+{NO_DEATH_BONUS}%)", self.bonus()),
+            n => format!("  no deaths   {:>6} ({n} deaths)", 0),
+        };
+
+        vec![
+            format!("score: {}", self.total()),
+            format!("  exploring   {:>6}", self.exploration()),
+            format!("  fighting    {:>6}", self.combat),
+            format!("  treasure    {:>6}", self.treasure),
+            format!("  bosses      {:>6}", self.bosses),
+            format!("  quick wins  {:>6}", self.quick),
+            bonus,
+        ]
+    }
+}
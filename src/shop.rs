@@ -0,0 +1,341 @@
+use crate::game::GameState;
+use crate::items::{self, Category, ItemKey};
+use crate::level::{Biome, Coords};
+use crate::loot::{self, LootEntry, Rarity};
+use crate::world::World;
+use crate::zone::ZoneType;
+use crate::Player;
+use anyhow::{bail, Result};
+use rand::prelude::*;
+
+/// how long a shop keeps its stock before it restocks.
+pub const RESTOCK_TIME: u32 = 100;
+/// the chance (out of 100) of a wandering merchant in a zone outside of Town.
+const MERCHANT_CHANCE: u8 = 6;
+
+/// how likely each tier is in a shops stock. (common, uncommon, rare, legendary)
+const TOWN_TIERS: [u8; 4] = [50, 32, 15, 3];
+/// wandering merchants carry less, but what they have is better.
+const MERCHANT_TIERS: [u8; 4] = [30, 40, 24, 6];
+
+const SHOPKEEPERS: [&str; 6] = ["Marta", "Old Bren", "Ilsa", "Tobin", "Greta", "Fennick"];
+const MERCHANTS: [&str; 4] = ["Sabir", "Odile", "Quill", "Yusra"];
+
+/// how much a shop charges over an items value, in quarters. (common, uncommon, rare, legendary)
+const MARKUP: [u16; 4] = [4, 5, 6, 8];
+
+/// what the shop charges for an item, rarer items cost more than their value.
+pub fn price(name: &str) -> u32 {
+    let Some(def) = items::lookup(name) else {
+        return 0;
+    };
+
+    let markup = match loot::rarity(name) {
+        Rarity::Common => MARKUP[0],
+        Rarity::Uncommon => MARKUP[1],
+        Rarity::Rare => MARKUP[2],
+        Rarity::Legendary => MARKUP[3],
+    };
+
+    (def.value as u32 * markup as u32 / 4).max(1)
+}
+
+/// what the shop pays for an item, half of what it would sell it for. key items can't be sold.
+pub fn offer(name: &str) -> Option<u32> {
+    let def = items::lookup(name)?;
+
+    (def.category != Category::Key && def.value > 0).then(|| (price(name) / 2).max(1))
+}
+
+/// someone to buy from and sell to. every Shelter and Sanctuary in Town has a shop, and wandering
+/// merchants turn up now and then everywhere else.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shop {
+    /// who runs the shop.
+    pub keeper: &'static str,
+    /// true for a wandering merchant.
+    pub wandering: bool,
+    /// the items that can only be found in the shops biome, they show up in its stock.
+    table: &'static [LootEntry],
+    seed: u64,
+    /// what's for sale, as (item, count).
+    pub stock: Vec<(ItemKey, u8)>,
+    /// which restock the current stock is from. (the world clock / RESTOCK_TIME)
+    restocked: Option<u32>,
+}
+
+impl Shop {
+    /// opens the shop in a zone, or None if there isn't one.
+    pub fn new(biome: Biome, kind: &ZoneType, seed: u64) -> Option<Self> {
+        let mut rng = StdRng::seed_from_u64(seed.rotate_left(41));
+
+        let wandering = match kind {
+            ZoneType::Shelter | ZoneType::Sanctuary if matches!(biome, Biome::Town) => false,
+            ZoneType::OpenPath | ZoneType::Coredor | ZoneType::Mountains
+                if !matches!(biome, Biome::Town) && rng.gen_range(0..100) < MERCHANT_CHANCE =>
+            {
+                true
+            }
+            _ => return None,
+        };
+
+        let keeper = match wandering {
+            true => MERCHANTS.choose(&mut rng),
+            false => SHOPKEEPERS.choose(&mut rng),
+        };
+
+        Some(Self {
+            keeper: keeper.copied().unwrap_or(SHOPKEEPERS[0]),
+            wandering,
+            table: loot::loot_table(biome),
+            seed,
+            stock: Vec::new(),
+            restocked: None,
+        })
+    }
+
+    /// what the shop is called when the player finds it.
+    pub fn title(&self) -> String {
+        match self.wandering {
+            true => format!("{}, a wandering merchant", self.keeper),
+            false => format!("{}'s shop", self.keeper),
+        }
+    }
+
+    /// fills the shelves with new stock if it has been RESTOCK_TIME since the last time. anything
+    /// the player sold is gone once the shop restocks.
+    pub fn restock(&mut self, clock: u32) {
+        let period = clock / RESTOCK_TIME;
+
+        if self.restocked == Some(period) {
+            return;
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed ^ (period as u64).rotate_left(17));
+        let (tiers, (min, max)) = match self.wandering {
+            true => (&MERCHANT_TIERS, (3, 5)),
+            false => (&TOWN_TIERS, (6, 9)),
+        };
+
+        self.stock.clear();
+        self.restocked = Some(period);
+
+        for _ in 0..rng.gen_range(min..=max) {
+            let key = loot::roll_item_from(self.table, tiers, &mut rng);
+            let count = match loot::rarity(&key.name) {
+                Rarity::Common => rng.gen_range(2..=5),
+                Rarity::Uncommon => rng.gen_range(1..=2),
+                _ => 1,
+            };
+
+            self.stock_up(key, count);
+        }
+    }
+
+    /// adds `count` of an item to the stock.
+    fn stock_up(&mut self, key: ItemKey, count: u8) {
+        match self
+            .stock
+            .iter_mut()
+            .find(|(item, _)| item.name == key.name)
+        {
+            Some((_, n)) => *n = n.saturating_add(count),
+            None => self.stock.push((key, count)),
+        }
+    }
+
+    /// what the shop has for sale, one line per item.
+    pub fn listing(&self, gold: u32) -> Vec<String> {
+        let mut lines = vec![format!("{} (you have {gold} gold):", self.title())];
+
+        if self.stock.is_empty() {
+            lines.push("  the shelves are bare.".to_string());
+        }
+
+        for (key, count) in &self.stock {
+            lines.push(format!(
+                "  {} x{count} - {} gold ({})",
+                key.name,
+                price(&key.name),
+                loot::rarity(&key.name)
+            ));
+        }
+
+        lines
+    }
+
+    /// sells `n` of an item to the player. if the player runs out of room part way, they only pay
+    /// for what they could carry.
+    pub fn buy(&mut self, name: &str, n: u8, player: &mut Player) -> Result<String> {
+        let Some(i) = self
+            .stock
+            .iter()
+            .position(|(key, _)| key.name.eq_ignore_ascii_case(name))
+        else {
+            bail!("{} doesn't sell \"{name}\". try list", self.keeper);
+        };
+
+        let (key, count) = self.stock[i].clone();
+        let cost = price(&key.name);
+
+        if count < n {
+            bail!("{} only has {count} {}.", self.keeper, key.name);
+        }
+
+        if player.gold < cost * n as u32 {
+            bail!(
+                "{} {} costs {} gold, you only have {}.",
+                n,
+                key.name,
+                cost * n as u32,
+                player.gold
+            );
+        }
+
+        let Some(item) = items::make(&key) else {
+            bail!("{} doesn't sell \"{name}\".", self.keeper);
+        };
+
+        let mut bought = 0;
+
+        for _ in 0..n {
+            if let Err(e) = player.inventory.add(item.as_ref()) {
+                if bought == 0 {
+                    return Err(e);
+                }
+
+                break;
+            }
+
+            bought += 1;
+        }
+
+        player.gold -= cost * bought as u32;
+
+        match count - bought {
+            0 => {
+                self.stock.remove(i);
+            }
+            left => self.stock[i].1 = left,
+        }
+
+        Ok(format!(
+            "you buy {bought} {} for {} gold. ({} left)",
+            key.name,
+            cost * bought as u32,
+            player.gold
+        ))
+    }
+
+    /// buys `n` of an item from the player. sold items go out of the players inventory and onto
+    /// the shelves until the next restock.
+    pub fn sell(&mut self, name: &str, n: u8, player: &mut Player) -> Result<String> {
+        let Some(key) = player.inventory.find(name) else {
+            bail!("you don't have a \"{name}\". try inventory");
+        };
+
+        let Some(offer) = offer(&key.name) else {
+            bail!("{} has no use for the {}.", self.keeper, key.name);
+        };
+
+        player.inventory.remove_named(&key.name, n)?;
+        player.gold += offer * n as u32;
+        self.stock_up(ItemKey::new(&key.name), n);
+
+        Ok(format!(
+            "you sell {n} {} for {} gold. ({} total)",
+            key.name,
+            offer * n as u32,
+            player.gold
+        ))
+    }
+}
+
+/// trading with a shop. the shop stays in its zone, so this just remembers where it is.
+pub struct ShopState {
+    level: usize,
+    loc: Coords,
+    done: bool,
+}
+
+impl ShopState {
+    /// walks into the shop at `loc`, restocking it if it's due.
+    pub fn new(level: usize, loc: Coords, player: &Player, world: &mut World) -> Option<Self> {
+        let shop = world.levels[level][loc].shop.as_mut()?;
+        shop.restock(world.clock);
+
+        println!("you browse the wares of {}.", shop.title());
+        for line in shop.listing(player.gold) {
+            println!("{line}");
+        }
+        println!("(buy <item> [n], sell <item> [n], list, or leave)");
+
+        Some(Self {
+            level,
+            loc,
+            done: false,
+        })
+    }
+}
+
+/// splits "<item> [n]" into the items name and how many, which defaults to one.
+fn item_and_count<'a>(args: impl Iterator<Item = &'a str>) -> (String, u8) {
+    let mut words: Vec<&str> = args.collect();
+    let n = words.last().and_then(|n| n.parse::<u8>().ok());
+
+    if n.is_some() {
+        words.pop();
+    }
+
+    (words.join(" "), n.unwrap_or(1))
+}
+
+impl GameState for ShopState {
+    fn step(&mut self, cmd: &str, player: &mut Player, world: &mut World) -> bool {
+        let Some(shop) = world.levels[self.level][self.loc].shop.as_mut() else {
+            self.done = true;
+            return true;
+        };
+
+        let mut args = cmd.split_whitespace();
+
+        match args.next() {
+            Some("list" | "l") => {
+                for line in shop.listing(player.gold) {
+                    println!("{line}");
+                }
+            }
+            Some("buy" | "b") => match item_and_count(args) {
+                (_, 0) => println!("buy how many?"),
+                (name, n) => match shop.buy(&name, n, player) {
+                    Ok(line) => println!("{line}"),
+                    Err(e) => println!("{e}"),
+                },
+            },
+            Some("sell" | "s") => match item_and_count(args) {
+                (_, 0) => println!("sell how many?"),
+                (name, n) => match shop.sell(&name, n, player) {
+                    Ok(line) => println!("{line}"),
+                    Err(e) => println!("{e}"),
+                },
+            },
+            Some("inventory" | "inv" | "i") => {
+                for line in player.inventory.listing() {
+                    println!("{line}");
+                }
+            }
+            Some("leave" | "exit" | "bye") => {
+                println!("{} waves you off.", shop.keeper);
+                self.done = true;
+            }
+            Some(cmd) => println!("unknown command \"{cmd}\". (buy, sell, list, or leave)"),
+            None => {}
+        }
+
+        self.is_done()
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+}
@@ -0,0 +1,460 @@
+use crate::allies::Companion;
+use crate::battle_logic::{Effect, Spell};
+use crate::damage::{self, Damage, DamageType};
+use crate::player::Stats;
+use crate::status::{Status, StatusKind};
+use anyhow::{bail, Result};
+use std::fmt;
+use std::sync::Arc;
+
+/// how much mana a caster gets back at the end of every round of battle.
+pub const MANA_REGEN: u8 = 1;
+
+/// the names of every spell the player can learn, in the order temples teach them.
+pub const BOOK: [&str; 6] = [
+    "Fireball",
+    "Heal",
+    "Shield",
+    "Poison Cloud",
+    "Haste",
+    "Call Wolf",
+];
+
+/// the element a spell draws on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum School {
+    Fire,
+    Frost,
+    Nature,
+    Light,
+    Shadow,
+    Arcane,
+}
+
+impl fmt::Display for School {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            School::Fire => write!(f, "fire"),
+            School::Frost => write!(f, "frost"),
+            School::Nature => write!(f, "nature"),
+            School::Light => write!(f, "light"),
+            School::Shadow => write!(f, "shadow"),
+            School::Arcane => write!(f, "arcane"),
+        }
+    }
+}
+
+/// who a spell can be cast on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetMode {
+    /// one enemy of the caster's choosing.
+    Single,
+    /// every enemy at once.
+    All,
+    /// only the caster.
+    TheSelf,
+    /// one of the casters allies, or the caster themself.
+    Ally,
+}
+
+impl fmt::Display for TargetMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetMode::Single => write!(f, "one enemy"),
+            TargetMode::All => write!(f, "all enemies"),
+            TargetMode::TheSelf => write!(f, "self"),
+            TargetMode::Ally => write!(f, "an ally"),
+        }
+    }
+}
+
+/// the mana pool and spell cooldowns of anything that casts spells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mana {
+    /// current mana
+    pub mp: u8,
+    /// max mana
+    pub max: u8,
+    /// spells that can't be cast again yet, and how many rounds until they can.
+    cooldowns: Vec<(Arc<str>, u8)>,
+}
+
+impl Mana {
+    pub fn new(max: u8) -> Self {
+        Self {
+            mp: max,
+            max,
+            cooldowns: Vec::new(),
+        }
+    }
+
+    /// how many rounds until a spell can be cast again.
+    pub fn cooldown(&self, spell: &str) -> u8 {
+        self.cooldowns
+            .iter()
+            .find(|(name, _)| name.as_ref() == spell)
+            .map_or(0, |(_, rounds)| *rounds)
+    }
+
+    /// checks if the spell can be cast right now. the error says why not.
+    pub fn check(&self, spell: &dyn Spell) -> Result<()> {
+        let name = spell.name();
+
+        match self.cooldown(&name) {
+            0 if self.mp < spell.cost() => bail!(
+                "{name} costs {} mp, you only have {}.",
+                spell.cost(),
+                self.mp
+            ),
+            0 => Ok(()),
+            1 => bail!("{name} will be ready next round."),
+            n => bail!("{name} will be ready in {n} rounds."),
+        }
+    }
+
+    /// pays for a spell and starts its cooldown.
+    pub fn spend(&mut self, spell: &dyn Spell) -> Result<()> {
+        self.check(spell)?;
+        self.mp -= spell.cost();
+
+        if spell.cooldown() > 0 {
+            self.cooldowns.push((spell.name(), spell.cooldown()));
+        }
+
+        Ok(())
+    }
+
+    /// counts down cooldowns and regenerates some mana. called once per round.
+    pub fn tick(&mut self) {
+        for (_, rounds) in self.cooldowns.iter_mut() {
+            *rounds -= 1;
+        }
+
+        self.cooldowns.retain(|(_, rounds)| *rounds > 0);
+        self.restore(MANA_REGEN);
+    }
+
+    /// gives back mana, up to the max.
+    pub fn restore(&mut self, amount: u8) {
+        self.mp = self.mp.saturating_add(amount).min(self.max);
+    }
+
+    /// refills mana and ends all cooldowns.
+    pub fn refill(&mut self) {
+        self.mp = self.max;
+        self.cooldowns.clear();
+    }
+}
+
+impl fmt::Display for Mana {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} mp", self.mp, self.max)
+    }
+}
+
+/// a one line summary of a spell. (eg, "Fireball: fire, all enemies, 6 mp, 2 round cooldown")
+pub fn describe(spell: &dyn Spell) -> String {
+    let mut desc = format!(
+        "{}: {}, {}, {} mp",
+        spell.name(),
+        spell.school(),
+        spell.target_mode(),
+        spell.cost()
+    );
+
+    if spell.cooldown() > 0 {
+        desc.push_str(&format!(", {} round cooldown", spell.cooldown()));
+    }
+
+    desc
+}
+
+/// makes a spell from the spell book by name. used to teach spells to the player (from temples,
+/// scrolls, etc) and to cast the ones they know.
+pub fn learn(name: &str) -> Option<Box<dyn Spell>> {
+    let spell: Box<dyn Spell> = match name.to_lowercase().as_str() {
+        "fireball" => Box::new(Fireball),
+        "heal" => Box::new(Heal),
+        "shield" => Box::new(Ward),
+        "poison cloud" => Box::new(PoisonCloud),
+        "haste" => Box::new(Quicken),
+        "call wolf" => Box::new(CallWolf),
+        _ => return None,
+    };
+
+    Some(spell)
+}
+
+/// a plain damaging spell. used by enemies, who don't need the spell books extras.
+pub struct Bolt {
+    name: Arc<str>,
+    school: School,
+    /// added to the casters magical strength.
+    power: u8,
+}
+
+impl Bolt {
+    pub fn new(name: &str, school: School, power: u8) -> Self {
+        Self {
+            name: Arc::from(name),
+            school,
+            power,
+        }
+    }
+}
+
+/// a spell that puts a status effect on its target for a few turns. used by enemies.
+pub struct Hex {
+    name: Arc<str>,
+    school: School,
+    kind: StatusKind,
+    potency: u8,
+}
+
+impl Hex {
+    pub fn new(name: &str, school: School, kind: StatusKind, potency: u8) -> Self {
+        Self {
+            name: Arc::from(name),
+            school,
+            kind,
+            potency,
+        }
+    }
+}
+
+impl Spell for Hex {
+    fn name(&self) -> Arc<str> {
+        self.name.clone()
+    }
+
+    fn school(&self) -> School {
+        self.school
+    }
+
+    #[allow(unused_variables)]
+    fn cast(&mut self, caster: &Stats, target: &Stats) -> Effect {
+        // being stunned for more than a turn at a time is no fun.
+        let turns = match self.kind {
+            StatusKind::Stun => 1,
+            _ => 3,
+        };
+
+        Effect::Status(Box::new(Status::new(self.kind, turns, self.potency)))
+    }
+}
+
+/// magical damage from a caster with `power` added to their magical strength.
+fn blast(caster: &Stats, target: &Stats, power: u8) -> Effect {
+    Effect::Damage(Damage {
+        amount: damage::mitigate(caster.mg_str.saturating_add(power), target.mg_def),
+        kind: DamageType::Magical,
+        crit: false,
+    })
+}
+
+impl Spell for Bolt {
+    fn name(&self) -> Arc<str> {
+        self.name.clone()
+    }
+
+    fn school(&self) -> School {
+        self.school
+    }
+
+    fn cast(&mut self, caster: &Stats, target: &Stats) -> Effect {
+        blast(caster, target, self.power)
+    }
+}
+
+/// hits every enemy with fire.
+pub struct Fireball;
+
+impl Spell for Fireball {
+    fn name(&self) -> Arc<str> {
+        Arc::from("Fireball")
+    }
+
+    fn school(&self) -> School {
+        School::Fire
+    }
+
+    fn target_mode(&self) -> TargetMode {
+        TargetMode::All
+    }
+
+    fn cost(&self) -> u8 {
+        6
+    }
+
+    fn cooldown(&self) -> u8 {
+        2
+    }
+
+    fn cast(&mut self, caster: &Stats, target: &Stats) -> Effect {
+        blast(caster, target, 4)
+    }
+}
+
+/// mends wounds, scaling with magical strength.
+pub struct Heal;
+
+impl Spell for Heal {
+    fn name(&self) -> Arc<str> {
+        Arc::from("Heal")
+    }
+
+    fn school(&self) -> School {
+        School::Light
+    }
+
+    fn target_mode(&self) -> TargetMode {
+        TargetMode::Ally
+    }
+
+    fn cost(&self) -> u8 {
+        4
+    }
+
+    #[allow(unused_variables)]
+    fn cast(&mut self, caster: &Stats, target: &Stats) -> Effect {
+        Effect::Heal(caster.mg_str.saturating_add(6))
+    }
+}
+
+/// raises the casters defences for a few rounds.
+pub struct Ward;
+
+impl Spell for Ward {
+    fn name(&self) -> Arc<str> {
+        Arc::from("Shield")
+    }
+
+    fn school(&self) -> School {
+        School::Arcane
+    }
+
+    fn target_mode(&self) -> TargetMode {
+        TargetMode::TheSelf
+    }
+
+    fn cost(&self) -> u8 {
+        3
+    }
+
+    fn cooldown(&self) -> u8 {
+        4
+    }
+
+    #[allow(unused_variables)]
+    fn cast(&mut self, caster: &Stats, target: &Stats) -> Effect {
+        Effect::Status(Box::new(Status::new(
+            StatusKind::Shield,
+            3,
+            2 + caster.mg_str / 3,
+        )))
+    }
+}
+
+/// poisons every enemy.
+pub struct PoisonCloud;
+
+impl Spell for PoisonCloud {
+    fn name(&self) -> Arc<str> {
+        Arc::from("Poison Cloud")
+    }
+
+    fn school(&self) -> School {
+        School::Nature
+    }
+
+    fn target_mode(&self) -> TargetMode {
+        TargetMode::All
+    }
+
+    fn cost(&self) -> u8 {
+        5
+    }
+
+    fn cooldown(&self) -> u8 {
+        3
+    }
+
+    #[allow(unused_variables)]
+    fn cast(&mut self, caster: &Stats, target: &Stats) -> Effect {
+        Effect::Status(Box::new(Status::new(
+            StatusKind::Poison,
+            3,
+            1 + caster.mg_str / 4,
+        )))
+    }
+}
+
+/// speeds up an ally, letting them act more often.
+pub struct Quicken;
+
+impl Spell for Quicken {
+    fn name(&self) -> Arc<str> {
+        Arc::from("Haste")
+    }
+
+    fn school(&self) -> School {
+        School::Arcane
+    }
+
+    fn target_mode(&self) -> TargetMode {
+        TargetMode::Ally
+    }
+
+    fn cost(&self) -> u8 {
+        4
+    }
+
+    fn cooldown(&self) -> u8 {
+        5
+    }
+
+    #[allow(unused_variables)]
+    fn cast(&mut self, caster: &Stats, target: &Stats) -> Effect {
+        Effect::Status(Box::new(Status::new(StatusKind::Haste, 3, 0)))
+    }
+}
+
+/// calls a wolf to fight alongside the caster until the end of the battle. the wolf grows with the
+/// casters magic.
+pub struct CallWolf;
+
+impl Spell for CallWolf {
+    fn name(&self) -> Arc<str> {
+        Arc::from("Call Wolf")
+    }
+
+    fn school(&self) -> School {
+        School::Nature
+    }
+
+    fn target_mode(&self) -> TargetMode {
+        TargetMode::TheSelf
+    }
+
+    fn cost(&self) -> u8 {
+        6
+    }
+
+    fn cooldown(&self) -> u8 {
+        6
+    }
+
+    #[allow(unused_variables)]
+    fn cast(&mut self, caster: &Stats, target: &Stats) -> Effect {
+        let stats = Stats {
+            hp: caster.mg_str.saturating_mul(2).saturating_add(8),
+            str: (caster.mg_str / 2).saturating_add(3),
+            mg_str: 0,
+            def: 2,
+            mg_def: 1,
+            speed: 6,
+        };
+
+        Effect::Summon(Box::new(Companion::summoned("Wolf", stats)))
+    }
+}
@@ -0,0 +1,163 @@
+use crate::player::{Buff, BuffType, Stacking, StatusEffect, Tick};
+use std::fmt;
+use std::sync::Arc;
+
+/// the most a stacking effect's potency can build up to.
+pub const MAX_POTENCY: u8 = 12;
+
+/// while exploring, status effects tick once every this many world clock ticks.
+pub const EXPLORE_TICK_TIME: u32 = 3;
+
+/// the kinds of status effect there are. each one behaves differently, see Status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusKind {
+    /// deals `potency` damage every turn. stacks up.
+    Poison,
+    /// deals `potency` damage every turn and weakens defence.
+    Burn,
+    /// halves speed.
+    Freeze,
+    /// restores `potency` health every turn.
+    Regen,
+    /// the entity loses its turns.
+    Stun,
+    /// the entity's attacks often miss.
+    Blind,
+    /// raises physical and magical defence by `potency`.
+    Shield,
+    /// boosts speed by half.
+    Haste,
+}
+
+impl fmt::Display for StatusKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusKind::Poison => write!(f, "poison"),
+            StatusKind::Burn => write!(f, "burn"),
+            StatusKind::Freeze => write!(f, "freeze"),
+            StatusKind::Regen => write!(f, "regeneration"),
+            StatusKind::Stun => write!(f, "stun"),
+            StatusKind::Blind => write!(f, "blindness"),
+            StatusKind::Shield => write!(f, "shield"),
+            StatusKind::Haste => write!(f, "haste"),
+        }
+    }
+}
+
+/// a status effect that lasts for a number of turns.
+pub struct Status {
+    pub kind: StatusKind,
+    /// turns left
+    turns: u8,
+    /// how strong the effect is, what that means depends on the kind.
+    potency: u8,
+}
+
+impl Status {
+    pub fn new(kind: StatusKind, turns: u8, potency: u8) -> Self {
+        Self {
+            kind,
+            turns,
+            potency,
+        }
+    }
+}
+
+impl StatusEffect for Status {
+    fn display_name(&self) -> Arc<str> {
+        Arc::from(self.kind.to_string())
+    }
+
+    fn in_effect(&self) -> bool {
+        self.turns > 0
+    }
+
+    fn get_buff(&self) -> Buff {
+        match self.kind {
+            StatusKind::Burn => Buff {
+                def: BuffType::Const(-2),
+                ..Buff::none()
+            },
+            StatusKind::Freeze => Buff {
+                speed: BuffType::Multiplier(0.5),
+                ..Buff::none()
+            },
+            StatusKind::Shield => Buff {
+                def: BuffType::Const(self.potency as i16),
+                mg_def: BuffType::Const(self.potency as i16),
+                ..Buff::none()
+            },
+            StatusKind::Haste => Buff {
+                speed: BuffType::Multiplier(1.5),
+                ..Buff::none()
+            },
+            _ => Buff::none(),
+        }
+    }
+
+    fn accuracy(&self) -> f32 {
+        match self.kind {
+            StatusKind::Blind => 0.5,
+            _ => 1.0,
+        }
+    }
+
+    fn stacking(&self) -> Stacking {
+        match self.kind {
+            StatusKind::Poison => Stacking::Intensify,
+            _ => Stacking::Refresh,
+        }
+    }
+
+    fn turns(&self) -> u8 {
+        self.turns
+    }
+
+    fn potency(&self) -> u8 {
+        self.potency
+    }
+
+    fn merge(&mut self, turns: u8, potency: u8) {
+        self.turns = self.turns.max(turns);
+        self.potency = match self.stacking() {
+            Stacking::Intensify => self.potency.saturating_add(potency).min(MAX_POTENCY),
+            _ => self.potency.max(potency),
+        };
+    }
+
+    fn step(&mut self) -> Tick {
+        self.turns = self.turns.saturating_sub(1);
+
+        match self.kind {
+            StatusKind::Poison | StatusKind::Burn => Tick {
+                damage: self.potency,
+                ..Tick::default()
+            },
+            StatusKind::Regen => Tick {
+                heal: self.potency,
+                ..Tick::default()
+            },
+            StatusKind::Stun => Tick {
+                skip_turn: true,
+                ..Tick::default()
+            },
+            _ => Tick::default(),
+        }
+    }
+}
+
+/// ticks every effect, removing the ones that wear off. returns what each effect did as
+/// (name, tick, worn off). the caller applies the ticks, since only it knows who the effects are on.
+pub fn tick_all(effects: &mut Vec<Box<dyn StatusEffect>>) -> Vec<(Arc<str>, Tick, bool)> {
+    let ticks = effects
+        .iter_mut()
+        .map(|effect| {
+            let tick = effect.step();
+            (effect.display_name(), tick, !effect.in_effect())
+        })
+        .collect();
+
+    effects.retain(|effect| effect.in_effect());
+
+    ticks
+}
@@ -1,6 +1,7 @@
 use crate::{
     biome,
     enemies::boss::Boss,
+    fame::HallOfFame,
     level::{Biome, Level, LevelBuilder},
 };
 use anyhow::Result;
@@ -11,12 +12,18 @@ pub struct World {
     /// traded for)
     pub levels: Vec<Level>, // TODO: limit this size based on mem-size and how much a single level
                             // takes up. make it an array of size (mem-size/level-size).
+    /// in game time, counted in steps taken while exploring.
+    pub clock: u32,
+    /// everyone this device knows of who beat the game.
+    pub fame: HallOfFame,
 }
 
 impl World {
     pub fn new() -> Self {
         Self {
             levels: Vec::with_capacity(10),
+            clock: 0,
+            fame: HallOfFame::new(),
         }
     }
 
@@ -27,8 +34,10 @@ impl World {
         info!("generating a {biome} biome...");
 
         let boss = Boss::new(biome);
+        let minions = boss.minions();
         let level_builder = LevelBuilder::new();
-        let level = level_builder.build(boss)?;
+        let mut level = level_builder.build(boss)?;
+        level.boss_room.set_minions(Some(minions));
         self.levels.push(level);
 
         info!("biome created.");
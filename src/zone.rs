@@ -1,8 +1,12 @@
+use crate::allies::Hireling;
 use crate::biome;
 use crate::enemies::{Enemy, MobSpawner};
-use crate::items::Item;
 use crate::level::Biome;
+use crate::loot::Stash;
+use crate::shop::Shop;
 use kahuna::State;
+use std::fmt;
+use std::fmt::Formatter;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -18,6 +22,22 @@ pub enum ZoneType {
     Wall,
 }
 
+impl fmt::Display for ZoneType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ZoneType::Shelter => write!(f, "Shelter"),
+            ZoneType::OpenPath => write!(f, "Open Path"),
+            ZoneType::Coredor => write!(f, "Corridor"),
+            ZoneType::Temple => write!(f, "Temple"),
+            ZoneType::Mountains => write!(f, "Mountains"),
+            ZoneType::Sanctuary => write!(f, "Sanctuary"),
+            ZoneType::TreasureRoom => write!(f, "Treasure Room"),
+            ZoneType::BossEntry => write!(f, "Boss Portal"),
+            ZoneType::Wall => write!(f, "Wall"),
+        }
+    }
+}
+
 // TODO: change this to a zone trait with multiple structs that implement that trait
 
 /// a single area in a level
@@ -28,9 +48,14 @@ pub struct Zone {
     pub desc: Arc<str>,
     pub feild_type: ZoneType,
     pub enemies: Option<MobSpawner>,
-    pub loot: fn(&str) -> Option<Box<dyn Item>>,
-    // pub hidden_loot: Arc<[Box<dyn Item>]>,
-    // pub chest: Option<dyn Item>,
+    /// the items to be found here, None if nothing ever turns up in this zone.
+    pub loot: Option<Stash>,
+    /// true once the player has set foot in this zone.
+    pub visited: bool,
+    /// someone waiting here to join the players party.
+    pub companion: Option<&'static Hireling>,
+    /// a shop or wandering merchant to trade with.
+    pub shop: Option<Shop>,
 }
 
 impl Zone {
@@ -41,7 +66,10 @@ impl Zone {
             desc: Arc::from(""),
             feild_type: ZoneType::Wall,
             enemies: None,
-            loot: |item_name| None,
+            loot: None,
+            visited: false,
+            companion: None,
+            shop: None,
         }
     }
 
@@ -54,29 +82,29 @@ impl Zone {
     }
 
     pub fn desc(&mut self, desc: &str) {
-        self.welcome = Arc::from(desc);
+        self.desc = Arc::from(desc);
     }
 
     pub fn feild_type(&mut self, feild: ZoneType) {
         self.feild_type = feild;
-        // TODO: auto generate enemies, loot, and hidden_loot here.
     }
 
-    pub fn set_item(&mut self, call_back: fn(&str) -> Option<Box<dyn Item>>) {
-        self.loot = call_back;
+    /// the name to show the player. zones without a name fall back to their type.
+    pub fn title(&self) -> String {
+        if self.name.is_empty() {
+            self.feild_type.to_string()
+        } else {
+            self.name.to_string()
+        }
+    }
+
+    pub fn set_loot(&mut self, loot: Option<Stash>) {
+        self.loot = loot;
     }
 
     // pub fn enemies(&mut self, enemies: Vec<Box<dyn Enemy>>) {
     //     self.enemies = Arc::from(enemies);
     // }
-
-    // pub fn chest(&mut self, loot: Vec<dyn Item>) {
-    //     self.chest = Some(Arc::from(loot));
-    // }
-
-    // pub fn hidden_loot(&mut self, loot: Vec<Box<dyn Item>>) {
-    //     self.hidden_loot = Arc::from(loot);
-    // }
 }
 
 impl State for Zone {
@@ -96,7 +124,9 @@ pub struct BossRoom {
     /// the boss monster
     pub boss: Option<Box<dyn Enemy>>,
     /// if the boss has lakies, they go here. not all bosses will have minions so this field is represented as an Option.
-    pub minions: Option<Arc<[Box<dyn Enemy>]>>,
+    pub minions: Option<Vec<Box<dyn Enemy>>>,
+    /// true once the boss has been beaten. (boss is also None while the player is fighting it)
+    pub slain: bool,
 }
 
 impl BossRoom {
@@ -113,6 +143,7 @@ impl BossRoom {
             desc: default_desc,
             boss,
             minions: None,
+            slain: false,
         }
     }
 
@@ -128,7 +159,7 @@ impl BossRoom {
         self.desc = Arc::from(desc);
     }
 
-    pub fn set_minions(&mut self, minions: Option<Arc<[Box<dyn Enemy>]>>) {
+    pub fn set_minions(&mut self, minions: Option<Vec<Box<dyn Enemy>>>) {
         self.minions = minions;
     }
 
@@ -138,5 +169,6 @@ impl BossRoom {
 
     pub fn kill_boss(&mut self) {
         self.boss = None;
+        self.slain = true;
     }
 }